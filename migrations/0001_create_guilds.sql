@@ -0,0 +1,37 @@
+create table if not exists public.guilds (
+    guild_id bigint primary key,
+    is_setup boolean not null default false,
+
+    -- settings
+    utc smallint not null default 0,
+    lang text not null default 'en',
+    lang2 text,
+    auto_relog boolean not null default true,
+
+    -- ids
+    log_channel_id bigint,
+    counting_channel_id bigint,
+    log_msg_map jsonb not null default '{}'::jsonb,
+    last_scanned_msg_id bigint,
+    log_helper_msg_id bigint,
+
+    -- maps
+    daily_counts jsonb not null default '{}'::jsonb,
+
+    updated_at timestamptz not null default now()
+);
+
+-- the table predates the migrations on deployed bots, fill in any column it lacks
+alter table public.guilds
+    add column if not exists is_setup boolean not null default false,
+    add column if not exists utc smallint not null default 0,
+    add column if not exists lang text not null default 'en',
+    add column if not exists lang2 text,
+    add column if not exists auto_relog boolean not null default true,
+    add column if not exists log_channel_id bigint,
+    add column if not exists counting_channel_id bigint,
+    add column if not exists log_msg_map jsonb not null default '{}'::jsonb,
+    add column if not exists last_scanned_msg_id bigint,
+    add column if not exists log_helper_msg_id bigint,
+    add column if not exists daily_counts jsonb not null default '{}'::jsonb,
+    add column if not exists updated_at timestamptz not null default now();
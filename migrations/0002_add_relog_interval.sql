@@ -0,0 +1,2 @@
+alter table public.guilds
+    add column if not exists relog_interval integer not null default 5;
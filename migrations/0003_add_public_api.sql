@@ -0,0 +1,2 @@
+alter table public.guilds
+    add column if not exists public_api boolean not null default false;
@@ -0,0 +1,2 @@
+alter table public.guilds
+    add column if not exists webhooks jsonb not null default '[]'::jsonb;
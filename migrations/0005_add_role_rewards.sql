@@ -0,0 +1,33 @@
+alter table public.guilds
+    add column if not exists role_rewards jsonb not null default '[]'::jsonb;
+
+-- valid counts per user per month (in the guild's timezone), lifetime is the sum
+create table if not exists public.user_month_counts (
+    guild_id bigint not null,
+    user_id bigint not null,
+    month text not null, -- YYYY-MM
+    counts bigint not null default 0,
+    primary key (guild_id, user_id, month)
+);
+
+-- who currently holds a reward role because of the bot
+create table if not exists public.role_reward_holders (
+    guild_id bigint not null,
+    role_id bigint not null,
+    user_id bigint not null,
+    granted_at timestamptz not null default now(),
+    primary key (guild_id, role_id, user_id)
+);
+
+create table if not exists public.role_reward_audit (
+    id bigserial primary key,
+    guild_id bigint not null,
+    user_id bigint not null,
+    role_id bigint not null,
+    action text not null, -- granted | revoked | failed
+    reason text not null,
+    created_at timestamptz not null default now()
+);
+
+create index if not exists role_reward_audit_guild_idx
+    on public.role_reward_audit (guild_id, id desc);
@@ -0,0 +1,5 @@
+-- Every counting channel of a guild (ids, mode and daily counts each).
+-- The old per-guild columns keep mirroring the first (primary) channel,
+-- guilds that predate this column are moved over the next time they load.
+alter table public.guilds
+    add column if not exists channels jsonb not null default '[]'::jsonb;
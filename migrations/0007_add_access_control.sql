@@ -0,0 +1,3 @@
+-- manager roles and per-command role overrides, see `/setup permissions`
+alter table public.guilds
+    add column if not exists access jsonb not null default '{}'::jsonb;
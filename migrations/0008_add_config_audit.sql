@@ -0,0 +1,16 @@
+alter table public.guilds
+    add column if not exists audit_channel_id bigint;
+
+-- configuration changes made through the admin commands
+create table if not exists public.config_audit (
+    id bigserial primary key,
+    guild_id bigint not null,
+    user_id bigint not null,
+    command text not null, -- e.g. `setup timezone`
+    old_value text not null,
+    new_value text not null,
+    created_at timestamptz not null default now()
+);
+
+create index if not exists config_audit_guild_idx
+    on public.config_audit (guild_id, id desc);
@@ -0,0 +1,11 @@
+-- GuildData taken right before destructive operations, see `/setup restore`
+create table if not exists public.guild_snapshots (
+    id bigserial primary key,
+    guild_id bigint not null,
+    reason text not null, -- e.g. `setup reset`
+    data jsonb not null,
+    created_at timestamptz not null default now()
+);
+
+create index if not exists guild_snapshots_guild_idx
+    on public.guild_snapshots (guild_id, id desc);
@@ -0,0 +1,81 @@
+use crate::data::audit::{ConfigChange, add_config_audit};
+use crate::utils::{command_path, log_error, log_warn};
+use serenity::all::{
+    ChannelId, CommandInteraction, CreateAllowedMentions, CreateMessage, Timestamp,
+};
+use serenity::prelude::Context;
+use sqlx::PgPool;
+
+// Values are shown inline, long ones (channel lists...) get cut
+const VALUE_LEN: usize = 150;
+
+fn shorten(value: &str) -> String {
+    if value.chars().count() <= VALUE_LEN {
+        return value.to_string();
+    }
+    format!("{}…", value.chars().take(VALUE_LEN).collect::<String>())
+}
+
+/// One change as shown in the audit channel and `/setup history`
+pub fn format_change(change: &ConfigChange) -> String {
+    format!(
+        "<t:{}:R> <@{}> `/{}`\n-# {} → {}",
+        change.created_at,
+        change.user_id,
+        change.command,
+        shorten(&change.old_value),
+        shorten(&change.new_value)
+    )
+}
+
+/// Stores who changed what with `command`, then posts it to the audit channel if there's one
+pub async fn record(
+    ctx: &Context,
+    pool: &PgPool,
+    command: &CommandInteraction,
+    audit_channel_id: Option<u64>,
+    old_value: &str,
+    new_value: &str,
+) {
+    let Some(guild_id) = command.guild_id else {
+        return;
+    };
+    let change = ConfigChange {
+        user_id: command.user.id.get(),
+        command: command_path(command),
+        old_value: old_value.to_string(),
+        new_value: new_value.to_string(),
+        created_at: Timestamp::now().unix_timestamp(),
+    };
+
+    if let Err(e) = add_config_audit(
+        pool,
+        guild_id.get(),
+        change.user_id,
+        &change.command,
+        &change.old_value,
+        &change.new_value,
+    )
+    .await
+    {
+        log_error(&format!(
+            "Failed to audit `/{}` in Guild{guild_id}: {e}",
+            change.command
+        ));
+    }
+
+    if let Some(channel_id) = audit_channel_id
+        && let Err(e) = ChannelId::new(channel_id)
+            .send_message(
+                &ctx.http,
+                CreateMessage::new()
+                    .content(format!("📝 {}", format_change(&change)))
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await
+    {
+        log_warn(&format!(
+            "Failed to post audit entry in Guild{guild_id}: {e}"
+        ));
+    }
+}
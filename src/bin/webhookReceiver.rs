@@ -0,0 +1,65 @@
+// Local stand-in for a webhook receiver: prints every event and checks its signature.
+// `cargo run --bin webhookReceiver -- <secret> [port]`, then `/webhook add url:http://localhost:<port>`
+use axum::Router;
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() {
+    let mut args = env::args().skip(1);
+    let secret = args.next().expect("Usage: webhookReceiver <secret> [port]");
+    let port = args.next().unwrap_or_else(|| "8787".to_string());
+
+    let app = Router::new().route(
+        "/",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let secret = secret.clone();
+            async move { receive(&secret, &headers, &body) }
+        }),
+    );
+
+    let addr = format!("127.0.0.1:{port}");
+    let listener = TcpListener::bind(&addr)
+        .await
+        .expect("❌ Failed to bind TCP listener");
+    println!("👂 Listening for webhooks on http://{addr}");
+
+    axum::serve(listener, app)
+        .await
+        .expect("❌ Receiver crashed");
+}
+
+fn receive(secret: &str, headers: &HeaderMap, body: &[u8]) -> StatusCode {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("-")
+            .to_string()
+    };
+
+    let expected = {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    };
+    let valid = header("X-CountLogger-Signature") == expected;
+
+    println!(
+        "{} {} {}",
+        if valid { "✅" } else { "❌ bad signature" },
+        header("X-CountLogger-Event"),
+        String::from_utf8_lossy(body)
+    );
+
+    if valid {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::UNAUTHORIZED
+    }
+}
@@ -2,10 +2,59 @@ use crate::data::{BotData, BotDataKey};
 use crate::handlers;
 use crate::utils::{log_error, log_info, log_warn};
 use chrono::Utc;
+use once_cell::sync::Lazy;
 use serenity::Client;
-use serenity::all::GatewayIntents;
+use serenity::all::{ConnectionStage, GatewayIntents, ShardManager};
+use serenity::prelude::Context;
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+
+// Context of the latest gateway session, swapped on every ready
+static CONTEXT: Lazy<RwLock<Option<Context>>> = Lazy::new(|| RwLock::new(None));
+
+// Shard manager of the current client, so shutdown can close the gateway cleanly
+static SHARD_MANAGER: Lazy<Mutex<Option<Arc<ShardManager>>>> = Lazy::new(|| Mutex::new(None));
+
+pub async fn set_context(ctx: Context) {
+    *CONTEXT.write().await = Some(ctx);
+}
+
+/// Context for work running outside of event handlers (scheduler, web server)
+pub async fn current_context() -> Option<Context> {
+    CONTEXT.read().await.clone()
+}
+
+pub struct ShardStatus {
+    pub id: u32,
+    pub stage: ConnectionStage,
+    pub latency: Option<Duration>,
+}
+
+/// Stage of every shard of the current client, `None` before the client exists
+pub async fn shard_statuses() -> Option<Vec<ShardStatus>> {
+    let shard_manager = SHARD_MANAGER.lock().await.clone()?;
+    let runners = shard_manager.runners.lock().await;
+
+    let mut statuses: Vec<ShardStatus> = runners
+        .iter()
+        .map(|(id, info)| ShardStatus {
+            id: id.0,
+            stage: info.stage,
+            latency: info.latency,
+        })
+        .collect();
+    statuses.sort_by_key(|s| s.id);
+    Some(statuses)
+}
+
+pub async fn shutdown_shards() {
+    if let Some(shard_manager) = SHARD_MANAGER.lock().await.take() {
+        log_info("🔌 Closing Discord gateway…");
+        shard_manager.shutdown_all().await;
+    }
+}
 
 pub async fn run(token: String, pool: Pool<Postgres>) -> Result<(), Box<dyn std::error::Error>> {
     loop {
@@ -31,16 +80,26 @@ pub async fn run(token: String, pool: Pool<Postgres>) -> Result<(), Box<dyn std:
 
         {
             client.data.write().await.insert::<BotDataKey>(bot_data);
+            *SHARD_MANAGER.lock().await = Some(client.shard_manager.clone());
             log_info("✅ Bot startup complete, connecting to gateway…");
         }
 
-        if let Err(e) = client.start().await {
+        let result = client.start().await;
+        if crate::shutdown::is_shutting_down() {
+            log_info("🔌 Discord client stopped");
+            return Ok(());
+        }
+
+        if let Err(e) = result {
             log_error(&format!("❌ Discord gateway exited: {e}"));
         } else {
             log_warn("⚠️ Discord client exited without error");
         }
 
         log_info("🔁 Reconnecting in 10 seconds…");
-        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        tokio::select! {
+            _ = tokio::time::sleep(std::time::Duration::from_secs(10)) => {}
+            _ = crate::shutdown::token().cancelled_owned() => return Ok(()),
+        }
     }
 }
@@ -1,57 +1,61 @@
 use crate::data::BotData;
-use crate::utils::internal_err;
+use crate::utils::{command_lang, internal_err, slash_command, tr};
 use serenity::builder::*;
 use serenity::model::application::*;
 use serenity::prelude::*;
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("help").description("Full guide about this bot")
+    slash_command("help", "DESC_HELP")
 }
 
-pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
-    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content(r#"A bot that can log progress of a counting channel in your guild!
-
-## **-USAGE-**
-
-Setup and let it run, then it will logs counting history automatically.
-
-## **-COMMAND-**
-
-`/help` : Full guide about this bot
-`/ping` : 🏓Pong! Shows basic stats
-
-### **(Admin only)**
-`/setup info` : Show info of current setup
-`/setup reset` : Reset entire current setup
-`/setup channels` `[log_channel]` `[counting_channel]` : Set each specified channel as current
-`/setup timezone` `[utc]` : Set timezone for logging clarity
-`/setup language` `[lang]` `[lang2: OPTIONAL]` : Set language for logging clarity
-`/relog start` : Fetch new and update all logs from the start
-`/relog formatonly`: Refresh and update only format for logs
-`/relog end` : Cancel on-going relog session
-`/relog auto toggle` : Toggle auto update logging activity
-`/message purge` `[total_messages: OPTIONAL]` `[include_users: OPTIONAL]` : Delete all (or specified amount) of this bot's (or also other users) message from log_channel
-
-## **-FORMAT-**
-
-```
-## 📊 Count Log
-## `Year <YYYY> (<part>)`
-`Date (<UTC>) : Sum`
-`(5 min update)`
-`<MM>-<DD>` <total_count> (+<count>)
-```
-
-## **-RECOMMENDED-**
+// Discord caps message content at 2000 characters
+const MESSAGE_LIMIT: usize = 2000;
+
+// Splits on line breaks so no message goes over the limit
+fn split_message(text: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    for line in text.lines() {
+        let part = parts.last_mut().unwrap();
+        if !part.is_empty() && part.chars().count() + line.chars().count() + 1 > MESSAGE_LIMIT {
+            parts.push(String::new());
+        }
+        let part = parts.last_mut().unwrap();
+        part.push_str(line);
+        part.push('\n');
+    }
+    parts
+}
 
-- Please use this bot alongside actual counting checking bot like `Countr` or others, as this bot only purpose is to log counting history.
-- Setup `[log_channel]` on an empty channel. It is dangerous to set this on a channel with message history.
-- Do `/relog` to refresh and see changes everytime after done `/setup` new configurations.
-"#)
-            .flags(InteractionResponseFlags::EPHEMERAL)
-    )).await {
+pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
+    let help = tr("HELP", &command_lang(&command, bot_data).await, &[]);
+    let mut parts = split_message(&help).into_iter();
+
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(parts.next().unwrap_or_default())
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
+        )
+        .await
+    {
         internal_err(&ctx, &command, &e.to_string()).await;
+        return;
+    }
+
+    for part in parts {
+        if let Err(e) = command
+            .create_followup(
+                &ctx.http,
+                CreateInteractionResponseFollowup::new()
+                    .content(part)
+                    .ephemeral(true),
+            )
+            .await
+        {
+            internal_err(&ctx, &command, &e.to_string()).await;
+        }
     }
 }
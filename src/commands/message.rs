@@ -1,40 +1,145 @@
+use crate::audit::record;
+use crate::commands::relog::snowflake_at;
+use crate::components::confirm;
 use crate::data::{BotData, load_guild_data};
-use crate::utils::{check_admin, internal_err};
+use crate::utils::{
+    check_admin, delete_messages, internal_err, is_bulk_deletable, reply_lang, slash_command,
+    slash_option, tr,
+};
+use chrono::{DateTime, NaiveDate};
+use regex::Regex;
 use serenity::all::*;
 use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::time::{Duration, Instant, sleep};
+use tokio::time::{Duration, Instant};
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("message")
-        .description("Message bundled commands related to CountLogger")
-        .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "purge",
-                "Purge all messages from CountLogger in log_channel",
-            )
+    slash_command("message", "DESC_MESSAGE").add_option(
+        slash_option(CommandOptionType::SubCommand, "purge", "DESC_MESSAGE_PURGE")
             .add_sub_option(
-                CreateCommandOption::new(
+                slash_option(
                     CommandOptionType::Integer,
-                    "total_message",
-                    "Customize number of messages to purge",
+                    "total_messages",
+                    "DESC_MESSAGE_PURGE_TOTAL",
                 )
+                .min_int_value(1)
                 .max_int_value(500)
                 .required(false),
             )
             .add_sub_option(
-                CreateCommandOption::new(
+                slash_option(
                     CommandOptionType::Boolean,
                     "include_users",
-                    "Also purge other user messages",
+                    "DESC_MESSAGE_PURGE_INCLUDE_USERS",
+                )
+                .required(false),
+            )
+            .add_sub_option(
+                slash_option(
+                    CommandOptionType::User,
+                    "author",
+                    "DESC_MESSAGE_PURGE_AUTHOR",
+                )
+                .required(false),
+            )
+            .add_sub_option(
+                slash_option(
+                    CommandOptionType::String,
+                    "content",
+                    "DESC_MESSAGE_PURGE_CONTENT",
+                )
+                .required(false),
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::String, "from", "DESC_MESSAGE_PURGE_FROM")
+                    .required(false),
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::String, "to", "DESC_MESSAGE_PURGE_TO")
+                    .required(false),
+            )
+            .add_sub_option(
+                slash_option(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "DESC_MESSAGE_PURGE_CHANNEL",
                 )
                 .required(false),
             ),
+    )
+}
+
+struct PurgeFilter {
+    bot_id: UserId,
+    author: Option<UserId>,
+    include_users: bool,
+    content: Option<Regex>,
+    from: Option<i64>, // unix seconds, inclusive
+    to: Option<i64>,   // unix seconds, exclusive
+}
+impl PurgeFilter {
+    fn matches(&self, msg: &Message) -> bool {
+        let author_ok = match self.author {
+            Some(author) => msg.author.id == author,
+            None => self.include_users || msg.author.id == self.bot_id,
+        };
+        author_ok
+            && self
+                .to
+                .is_none_or(|to| msg.id.created_at().unix_timestamp() < to)
+            && self
+                .content
+                .as_ref()
+                .is_none_or(|re| re.is_match(&msg.content))
+    }
+
+    // Newer messages are past `to`, the walk starts right before it instead of at the newest
+    fn walk_start(&self) -> Option<MessageId> {
+        self.to
+            .and_then(|to| DateTime::from_timestamp(to, 0))
+            .map(snowflake_at)
+    }
+
+    // Whether messages that aren't the bot's own can match
+    fn deletes_users(&self) -> bool {
+        match self.author {
+            Some(author) => author != self.bot_id,
+            None => self.include_users,
+        }
+    }
+}
+
+#[derive(Default)]
+struct PurgeReport {
+    scanned: usize,
+    matched: usize,
+    deleted: usize,
+    single: usize, // too old to bulk delete
+}
+
+// Start of the day in the guild's timezone, as unix seconds
+fn day_start(date: &str, utc: i8) -> Option<i64> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp() - utc as i64 * 3600)
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: String) {
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
         )
+        .await
+    {
+        internal_err(ctx, command, &e.to_string()).await;
+    }
 }
 
 pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
-    if !check_admin(&ctx, &command).await {
+    if !check_admin(&ctx, &command, bot_data).await {
         return;
     }
 
@@ -42,80 +147,168 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
         let guild_id_u64 = guild_id.get();
         match load_guild_data(&bot_data.pool, guild_id_u64).await {
             Ok(guild_data) => {
+                let lang = reply_lang(&command, Some(&guild_data.settings.lang));
                 if let Some(top) = command.data.options.first() {
                     match top.name.as_str() {
                         "purge" => {
                             if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
-                                if let Some(log_ch_id) = guild_data.ids.log_channel_id {
-                                    let _ = command.defer(&ctx.http).await;
-
-                                    let log_channel = ChannelId::new(log_ch_id);
-
-                                    let progress_msg = log_channel
-                                        .send_message(
-                                            &ctx.http,
-                                            CreateMessage::new().content(
-                                                "🔄 Deletion in progress... this might take a while!",
-                                            ),
-                                        )
-                                        .await
-                                        .unwrap_or_default();
-
-                                    let total_messages = sub_options
-                                        .iter()
-                                        .find(|o| o.name == "total_messages")
-                                        .and_then(|o| o.value.as_i64());
-                                    let include_users = sub_options
+                                let option = |name: &str| {
+                                    sub_options
                                         .iter()
-                                        .find(|o| o.name == "include_users")
-                                        .and_then(|o| o.value.as_bool());
+                                        .find(|o| o.name == name)
+                                        .map(|o| &o.value)
+                                };
+
+                                // every log channel unless one counting channel was picked
+                                let only_channel =
+                                    option("channel").and_then(|v| v.as_channel_id());
+                                if let Some(channel_id) = only_channel
+                                    && guild_data.channel(channel_id.get()).is_none()
+                                {
+                                    respond(
+                                        &ctx,
+                                        &command,
+                                        tr(
+                                            "NOT_COUNTING_CHANNEL",
+                                            &lang,
+                                            &[("channel", &channel_id)],
+                                        ),
+                                    )
+                                    .await;
+                                    return;
+                                }
+                                let mut log_channels: Vec<ChannelId> = Vec::new();
+                                for channel in guild_data.channels.iter().filter(|c| {
+                                    only_channel.is_none_or(|id| {
+                                        c.ids.counting_channel_id == Some(id.get())
+                                    })
+                                }) {
+                                    // shared log channels are purged once
+                                    if let Some(id) = channel.ids.log_channel_id
+                                        && !log_channels.contains(&ChannelId::new(id))
+                                    {
+                                        log_channels.push(ChannelId::new(id));
+                                    }
+                                }
+                                if log_channels.is_empty() {
+                                    respond(&ctx, &command, tr("NO_LOG_CHANNEL", &lang, &[])).await;
+                                    return;
+                                }
+                                let mentions = log_channels
+                                    .iter()
+                                    .map(|id| format!("<#{id}>"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                let total_messages =
+                                    option("total_messages").and_then(|v| v.as_i64());
+                                let utc = guild_data.settings.utc;
 
-                                    if let Some(total) = total_messages {
-                                        if let Err(e) = delete_bot_messages(
+                                let content = match option("content")
+                                    .and_then(|v| v.as_str())
+                                    .map(Regex::new)
+                                {
+                                    Some(Ok(re)) => Some(re),
+                                    Some(Err(e)) => {
+                                        respond(
                                             &ctx,
-                                            &progress_msg.id,
-                                            log_channel,
-                                            Some(total),
-                                            include_users,
+                                            &command,
+                                            tr("PURGE_INVALID_REGEX", &lang, &[("error", &e)]),
                                         )
-                                        .await
-                                        {
-                                            internal_err(&ctx, &command, &e.to_string()).await;
-                                        }
+                                        .await;
                                         return;
-                                    } else {
-                                        if let Err(e) = delete_bot_messages(
-                                            &ctx,
-                                            &progress_msg.id,
-                                            log_channel,
-                                            None,
-                                            include_users,
-                                        )
-                                        .await
-                                        {
-                                            internal_err(&ctx, &command, &e.to_string()).await;
-                                        }
                                     }
-
-                                    let _ = log_channel
-                                        .edit_message(
-                                            &ctx.http,
-                                            progress_msg.id,
-                                            EditMessage::new().content("✅ Deletion Done!\n-# This message will delete automatically in 10 seconds"),
+                                    None => None,
+                                };
+                                let mut range = [None, None];
+                                for (i, name) in ["from", "to"].into_iter().enumerate() {
+                                    let Some(date) = option(name).and_then(|v| v.as_str()) else {
+                                        continue;
+                                    };
+                                    let Some(start) = day_start(date, utc) else {
+                                        respond(
+                                            &ctx,
+                                            &command,
+                                            tr("PURGE_INVALID_DATE", &lang, &[("name", &name)]),
                                         )
                                         .await;
+                                        return;
+                                    };
+                                    range[i] = Some(start);
+                                }
+                                let [from, to] = range;
 
-                                    sleep(Duration::from_secs(10)).await;
+                                let filter = PurgeFilter {
+                                    bot_id: ctx.cache.current_user().id,
+                                    author: option("author").and_then(|v| v.as_user_id()),
+                                    include_users: option("include_users")
+                                        .and_then(|v| v.as_bool())
+                                        .unwrap_or(false),
+                                    content,
+                                    from,
+                                    // `to` is the last day purged
+                                    to: to.map(|start| start + 24 * 3600),
+                                };
 
-                                    let _ = progress_msg.delete(&ctx.http).await;
+                                // other people's messages can't be recovered, so ask first
+                                if filter.deletes_users() {
+                                    let prompt =
+                                        tr("PURGE_CONFIRM", &lang, &[("channels", &mentions)]);
+                                    let label = tr("BUTTON_PURGE", &lang, &[]);
+                                    if !confirm(&ctx, &command, &lang, prompt, label).await {
+                                        return;
+                                    }
                                 } else {
-                                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                        CreateInteractionResponseMessage::new()
-                                            .content("❌ No `log_channel` detected\nPlease use `/setup channels` to setup necessary channels.")
-                                            .flags(InteractionResponseFlags::EPHEMERAL)
-                                    )).await {
+                                    let _ = command.defer_ephemeral(&ctx.http).await;
+                                }
+
+                                let content = match purge_messages(
+                                    &ctx,
+                                    &command,
+                                    &log_channels,
+                                    total_messages,
+                                    &filter,
+                                    &lang,
+                                )
+                                .await
+                                {
+                                    Ok(report) => {
+                                        record(
+                                            &ctx,
+                                            &bot_data.pool,
+                                            &command,
+                                            guild_data.settings.audit_channel_id,
+                                            &format!(
+                                                "{} matching messages in {mentions}",
+                                                report.matched
+                                            ),
+                                            &format!("{} deleted", report.deleted),
+                                        )
+                                        .await;
+                                        tr(
+                                            "PURGE_DONE",
+                                            &lang,
+                                            &[
+                                                ("deleted", &report.deleted),
+                                                ("matched", &report.matched),
+                                                ("scanned", &report.scanned),
+                                                ("channels", &mentions),
+                                                ("single", &report.single),
+                                            ],
+                                        )
+                                    }
+                                    Err(e) => {
                                         internal_err(&ctx, &command, &e.to_string()).await;
+                                        return;
                                     }
+                                };
+                                if let Err(e) = command
+                                    .edit_response(
+                                        &ctx.http,
+                                        EditInteractionResponse::new().content(content),
+                                    )
+                                    .await
+                                {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
                                 }
                             }
                         }
@@ -126,7 +319,11 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     &ctx.http,
                                     CreateInteractionResponse::Message(
                                         CreateInteractionResponseMessage::new()
-                                            .content("❓ Available options: `purge`")
+                                            .content(tr(
+                                                "AVAILABLE_OPTIONS",
+                                                &lang,
+                                                &[("options", &"`purge`")],
+                                            ))
                                             .flags(InteractionResponseFlags::EPHEMERAL),
                                     ),
                                 )
@@ -140,11 +337,17 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
             }
             Err(e) => {
                 internal_err(&ctx, &command, &e.to_string()).await;
-                if let Err(e2) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❗ Failed to fetch from Database\nPlease report the problem to developer...")
-                        .flags(InteractionResponseFlags::EPHEMERAL)
-                )).await {
+                if let Err(e2) = command
+                    .create_response(
+                        &ctx.http,
+                        CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .content(tr("DB_FETCH_FAILED", &reply_lang(&command, None), &[]))
+                                .flags(InteractionResponseFlags::EPHEMERAL),
+                        ),
+                    )
+                    .await
+                {
                     internal_err(&ctx, &command, &e2.to_string()).await;
                 }
             }
@@ -155,7 +358,7 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                 &ctx.http,
                 CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
-                        .content("❗ This command can only be used within a discord server!")
+                        .content(tr("NOT_IN_GUILD", &reply_lang(&command, None), &[]))
                         .flags(InteractionResponseFlags::EPHEMERAL),
                 ),
             )
@@ -166,17 +369,17 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
     }
 }
 
-async fn delete_bot_messages(
+// Walks each channel back from the end of the range, deleting what the filter matches page by
+// page, `max_delete` counts across all of them
+async fn purge_messages(
     ctx: &Context,
-    progress_msg: &MessageId,
-    channel_id: ChannelId,
+    command: &CommandInteraction,
+    channels: &[ChannelId],
     max_delete: Option<i64>,
-    include_users: Option<bool>,
-) -> serenity::Result<()> {
-    let progress_msg_id = progress_msg.get();
-
-    let mut last_message_id: Option<MessageId> = None;
-    let mut deleted = 0i64;
+    filter: &PurgeFilter,
+    lang: &str,
+) -> serenity::Result<PurgeReport> {
+    let mut report = PurgeReport::default();
 
     let mut last_update = Instant::now();
     let unix_time = SystemTime::now()
@@ -185,61 +388,159 @@ async fn delete_bot_messages(
         .as_secs();
     let discord_timestamp = format!("<t:{}:R>", unix_time);
 
-    'outer: loop {
-        let mut get_messages = GetMessages::new().limit(100);
-        if let Some(last_id) = last_message_id {
-            get_messages = get_messages.before(last_id);
-        }
-
-        let messages = channel_id.messages(&ctx.http, get_messages).await?;
-        if messages.is_empty() {
+    for &channel_id in channels {
+        if max_delete.is_some_and(|max| report.matched as i64 >= max) {
             break;
         }
+        let mut last_message_id = filter.walk_start();
 
-        for msg in &messages {
-            if msg.id == progress_msg_id {
-                continue;
+        loop {
+            let mut get_messages = GetMessages::new().limit(100);
+            if let Some(last_id) = last_message_id {
+                get_messages = get_messages.before(last_id);
             }
 
-            if let Some(max) = max_delete {
-                if deleted >= max {
-                    break 'outer;
-                }
+            let messages = channel_id.messages(&ctx.http, get_messages).await?;
+            if messages.is_empty() {
+                break;
             }
 
-            if include_users.unwrap_or(false)
-                || msg.author.id == ctx.http.get_current_user().await?.id
-            {
-                if msg.delete(&ctx.http).await.is_ok() {
-                    if last_update.elapsed() >= Duration::from_secs(5) {
-                        let _ = channel_id
-                            .edit_message(
-                                &ctx.http,
-                                progress_msg,
-                                EditMessage::new().content(if let Some(max) = max_delete { format!(
-                                    "🔄 Deleting in progress...\n🚮 Deleted: `{}/{}`\n-# Started {}",
-                                    &deleted, max, discord_timestamp
-                                )} else {format!(
-                                    "🔄 Deleting in progress...\n🚮 Deleted: `{}`\n-# Started {}",
-                                    &deleted, discord_timestamp
-                                )}),
-                            )
-                            .await;
-
-                        last_update = Instant::now();
-                    }
+            let mut batch = Vec::new();
+            let mut done = false;
+            for msg in &messages {
+                if max_delete.is_some_and(|max| report.matched as i64 >= max)
+                    || filter
+                        .from
+                        .is_some_and(|from| msg.id.created_at().unix_timestamp() < from)
+                {
+                    done = true;
+                    break;
+                }
 
-                    deleted += 1;
+                report.scanned += 1;
+                if filter.matches(msg) {
+                    report.matched += 1;
+                    batch.push(msg.id);
                 }
             }
+
+            report.single += batch.iter().filter(|id| !is_bulk_deletable(**id)).count();
+            report.deleted += delete_messages(&ctx.http, channel_id, &batch).await;
+
+            if done {
+                break;
+            }
+
+            if last_update.elapsed() >= Duration::from_secs(5) {
+                let _ = command
+                    .edit_response(
+                        &ctx.http,
+                        EditInteractionResponse::new().content(tr(
+                            "PURGE_PROGRESS",
+                            lang,
+                            &[
+                                (
+                                    "deleted",
+                                    &match max_delete {
+                                        Some(max) => format!("{}/{max}", report.deleted),
+                                        None => report.deleted.to_string(),
+                                    },
+                                ),
+                                ("started", &discord_timestamp),
+                            ],
+                        )),
+                    )
+                    .await;
+
+                last_update = Instant::now();
+            }
+
+            let new_last = messages.last().map(|m| m.id);
+            if new_last == last_message_id {
+                break;
+            }
+            last_message_id = new_last;
         }
+    }
 
-        let new_last = messages.last().map(|m| m.id);
-        if new_last == last_message_id {
-            break;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(author: u64, sent_at: i64, content: &str) -> Message {
+        let mut msg = Message::default();
+        msg.id = snowflake_at(DateTime::from_timestamp(sent_at, 0).unwrap());
+        msg.author.id = UserId::new(author);
+        msg.content = content.to_string();
+        msg
+    }
+
+    fn filter() -> PurgeFilter {
+        PurgeFilter {
+            bot_id: UserId::new(1),
+            author: None,
+            include_users: false,
+            content: None,
+            from: None,
+            to: None,
         }
-        last_message_id = new_last;
     }
 
-    Ok(())
+    #[test]
+    fn only_the_bots_messages_by_default() {
+        let filter = filter();
+        assert!(filter.matches(&message(1, 1_700_000_000, "log")));
+        assert!(!filter.matches(&message(2, 1_700_000_000, "42")));
+        assert!(!filter.deletes_users());
+
+        let filter = PurgeFilter {
+            include_users: true,
+            ..self::filter()
+        };
+        assert!(filter.matches(&message(2, 1_700_000_000, "42")));
+        assert!(filter.deletes_users());
+
+        let filter = PurgeFilter {
+            author: Some(UserId::new(2)),
+            ..self::filter()
+        };
+        assert!(!filter.matches(&message(1, 1_700_000_000, "log")));
+        assert!(filter.matches(&message(2, 1_700_000_000, "42")));
+        assert!(filter.deletes_users());
+    }
+
+    #[test]
+    fn content_and_end_of_range_filter() {
+        let filter = PurgeFilter {
+            content: Some(Regex::new("^#").unwrap()),
+            to: Some(1_700_000_000),
+            ..self::filter()
+        };
+        assert!(filter.matches(&message(1, 1_699_999_999, "## Count Log")));
+        assert!(!filter.matches(&message(1, 1_699_999_999, "Count Log")));
+        assert!(!filter.matches(&message(1, 1_700_000_000, "## Count Log")));
+    }
+
+    #[test]
+    fn walk_starts_before_the_end_of_range() {
+        assert_eq!(filter().walk_start(), None);
+
+        let filter = PurgeFilter {
+            to: Some(1_700_000_000),
+            ..filter()
+        };
+        let start = filter.walk_start().unwrap();
+        assert!(message(1, 1_699_999_999, "").id < start);
+        assert!(message(1, 1_700_000_000, "").id >= start);
+    }
+
+    #[test]
+    fn days_start_in_the_guild_timezone() {
+        assert_eq!(day_start("2024-01-02", 0), Some(1_704_153_600));
+        assert_eq!(day_start("2024-01-02", 9), Some(1_704_153_600 - 9 * 3600));
+        assert_eq!(day_start("2024-13-02", 0), None);
+    }
 }
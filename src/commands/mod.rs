@@ -1,11 +1,14 @@
 use crate::data::BotData;
 use serenity::all::*;
+use tracing::Instrument;
 
 pub mod help;
 pub mod message;
 pub mod ping;
 pub mod relog;
+pub mod reward;
 pub mod setup;
+pub mod webhook;
 
 macro_rules! command_map {
     ( $( $name:literal => $module:ident ),* $(,)? ) => {{
@@ -44,16 +47,29 @@ pub async fn handle(ctx: Context, command: CommandInteraction, bot_data: &BotDat
         "setup" => setup,
         "relog" => relog,
         "message" => message,
+        "webhook" => webhook,
+        "reward" => reward,
     };
 
     if let Some(handler) = commands.get(command.data.name.as_str()) {
-        handler(ctx, command, bot_data).await;
+        let span = tracing::info_span!(
+            "command",
+            guild_id = command.guild_id.map(|id| id.get()),
+            command = %command.data.name,
+            user_id = command.user.id.get(),
+        );
+        handler(ctx, command, bot_data).instrument(span).await;
     } else {
+        let lang = crate::utils::command_lang(&command, bot_data).await;
         let _ = command
             .create_response(
                 &ctx.http,
                 CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new().content("❓ Unknown command"),
+                    CreateInteractionResponseMessage::new().content(crate::utils::tr(
+                        "UNKNOWN_COMMAND",
+                        &lang,
+                        &[],
+                    )),
                 ),
             )
             .await;
@@ -1,12 +1,13 @@
+use crate::data::BotData;
 use crate::data::load_all_data;
-use crate::{data::BotData, utils::internal_err};
+use crate::utils::{command_lang, internal_err, slash_command, tr};
 use serenity::builder::*;
 use serenity::model::application::*;
 use serenity::prelude::*;
 use std::time::Instant;
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("ping").description("🏓Pong! Shows basic stats")
+    slash_command("ping", "DESC_PING")
 }
 
 pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
@@ -29,9 +30,14 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
     )
     .to_string();
 
-    let reply = format!(
-        "🏓 Pong!\nLatency: `{}ms`\nServers: `{}`\nUptime: `{}`",
-        latency_ms, servers_count, uptime
+    let reply = tr(
+        "PING_REPLY",
+        &command_lang(&command, bot_data).await,
+        &[
+            ("latency", &latency_ms),
+            ("servers", &servers_count),
+            ("uptime", &uptime),
+        ],
     );
 
     if let Err(e) = command
@@ -1,49 +1,86 @@
-use crate::data::structs::GuildData;
-use crate::data::{BotData, load_all_data, load_guild_data, save_guild_data};
+use crate::audit::record;
+use crate::components::confirm;
+use crate::data::snapshots::save_snapshot;
+use crate::data::structs::{CountMode, CountingChannel, GuildData, GuildSettings, WebhookEvent};
+use crate::data::{BotData, load_guild_data, lock_guild, save_guild_data, update_guild_data};
+use crate::permissions::preflight;
 use crate::utils::*;
 use chrono::*;
 use once_cell::sync::Lazy;
+use serde_json::json;
 use serenity::all::*;
 use std::collections::BTreeSet;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
-use tokio::time::{Duration, Instant, MissedTickBehavior, interval, sleep};
+use tokio::time::{Duration, Instant, sleep};
 use tokio_util::sync::CancellationToken;
 
 pub fn register() -> CreateCommand {
-    CreateCommand::new("relog")
-        .description("Relog bundled commands")
-        .add_option(CreateCommandOption::new(
-            CommandOptionType::SubCommand,
-            "start",
-            "Fetch new and update all logs from the start",
-        ))
-        .add_option(CreateCommandOption::new(
-            CommandOptionType::SubCommand,
-            "formatonly",
-            "Refresh and update only format for logs",
-        ))
-        .add_option(CreateCommandOption::new(
+    slash_command("relog", "DESC_RELOG")
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "start", "DESC_RELOG_START")
+                .add_sub_option(channel_option()),
+        )
+        .add_option(
+            slash_option(
+                CommandOptionType::SubCommand,
+                "formatonly",
+                "DESC_RELOG_FORMATONLY",
+            )
+            .add_sub_option(channel_option()),
+        )
+        .add_option(slash_option(
             CommandOptionType::SubCommand,
             "cancel",
-            "Cancel on-going relog session",
+            "DESC_RELOG_CANCEL",
         ))
         .add_option(
-            CreateCommandOption::new(
+            slash_option(
                 CommandOptionType::SubCommandGroup,
                 "auto",
-                "Relog update automation",
+                "DESC_RELOG_AUTO",
             )
-            .add_sub_option(CreateCommandOption::new(
+            .add_sub_option(slash_option(
                 CommandOptionType::SubCommand,
                 "toggle",
-                "Toggle auto update activity",
-            )),
+                "DESC_RELOG_AUTO_TOGGLE",
+            ))
+            .add_sub_option(
+                slash_option(
+                    CommandOptionType::SubCommand,
+                    "interval",
+                    "DESC_RELOG_AUTO_INTERVAL",
+                )
+                .add_sub_option(
+                    slash_option(
+                        CommandOptionType::Integer,
+                        "minutes",
+                        "DESC_RELOG_AUTO_INTERVAL_MINUTES",
+                    )
+                    .min_int_value(MIN_RELOG_INTERVAL as u64)
+                    .max_int_value(MAX_RELOG_INTERVAL as u64)
+                    .required(true),
+                ),
+            ),
         )
 }
 
+fn channel_option() -> CreateCommandOption {
+    slash_option(CommandOptionType::Channel, "channel", "DESC_RELOG_CHANNEL").required(false)
+}
+
+// Start of every relog progress message, so leftovers can be recognized
+pub const PROGRESS_PREFIX: &str = "🔄 Relog in progress";
+
+pub const MIN_RELOG_INTERVAL: i32 = 1;
+pub const MAX_RELOG_INTERVAL: i32 = 1440;
+
+// Logged days from which `/relog start` asks before rescanning
+const LARGE_RELOG_DAYS: usize = 365;
+
 #[derive(Clone)]
 pub struct RelogState {
     pub lock: Arc<Mutex<()>>,
@@ -53,19 +90,115 @@ pub struct RelogState {
 static RELOG_STATES: Lazy<Mutex<HashMap<u64, RelogState>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// Tags every relog (manual or auto) so its log lines can be followed in the span's `relog_session`
+static NEXT_RELOG_SESSION: AtomicU64 = AtomicU64::new(1);
+
+fn next_relog_session() -> u64 {
+    NEXT_RELOG_SESSION.fetch_add(1, Ordering::Relaxed)
+}
+
 async fn get_relog_state(guild_id: u64) -> RelogState {
     let mut map = RELOG_STATES.lock().await;
 
-    map.entry(guild_id)
-        .or_insert_with(|| RelogState {
-            lock: Arc::new(Mutex::new(())),
-            cancel_token: CancellationToken::new(),
-        })
-        .clone()
+    let state = map.entry(guild_id).or_insert_with(|| RelogState {
+        lock: Arc::new(Mutex::new(())),
+        cancel_token: CancellationToken::new(),
+    });
+
+    // a previous `/relog cancel` leaves the token cancelled for good
+    if state.cancel_token.is_cancelled() && !crate::shutdown::is_shutting_down() {
+        state.cancel_token = CancellationToken::new();
+    }
+
+    state.clone()
+}
+
+/// The guild's relog lock, also held by anything else that rewrites its setup wholesale
+pub async fn relog_lock(guild_id: u64) -> Arc<Mutex<()>> {
+    Arc::clone(&get_relog_state(guild_id).await.lock)
+}
+
+/// Number of relog sessions currently holding their guild lock
+pub async fn running_relogs() -> usize {
+    RELOG_STATES
+        .lock()
+        .await
+        .values()
+        .filter(|state| state.lock.try_lock().is_err())
+        .count()
+}
+
+/// Runs a full relog (or format only) in the background without an interaction,
+/// returns false if the guild already has one running
+pub async fn spawn_relog(
+    ctx: Context,
+    bot_data: Arc<BotData>,
+    guild_id_u64: u64,
+    formatonly: bool,
+) -> bool {
+    let state = get_relog_state(guild_id_u64).await;
+    let Ok(guard) = Arc::clone(&state.lock).try_lock_owned() else {
+        return false;
+    };
+
+    tokio::spawn(async move {
+        let _guard = guard;
+        let mut guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+            Ok(guild_data) => guild_data,
+            Err(e) => {
+                log_error(&format!(
+                    "❌ Cannot load data from Guild{guild_id_u64}: {e}"
+                ));
+                return;
+            }
+        };
+        let _ = relog_start(
+            &ctx,
+            None,
+            &bot_data,
+            guild_id_u64,
+            &mut guild_data,
+            state.cancel_token,
+            formatonly,
+            None,
+        )
+        .await;
+    });
+
+    true
+}
+
+/// Cancels every relog session, returns how many were running
+pub async fn cancel_all_relogs() -> usize {
+    let map = RELOG_STATES.lock().await;
+
+    let mut running = 0;
+    for state in map.values() {
+        if state.lock.try_lock().is_err() {
+            running += 1;
+        }
+        state.cancel_token.cancel();
+    }
+
+    running
+}
+
+/// Waits until no relog session holds its guild lock
+pub async fn wait_for_relogs() {
+    let locks: Vec<Arc<Mutex<()>>> = RELOG_STATES
+        .lock()
+        .await
+        .values()
+        .map(|state| Arc::clone(&state.lock))
+        .collect();
+
+    for lock in locks {
+        let _ = lock.lock().await;
+    }
 }
 
 pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
-    if !check_admin(&ctx, &command).await {
+    if !check_admin(&ctx, &command, bot_data).await {
         return;
     }
 
@@ -73,12 +206,19 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
         let guild_id_u64 = guild_id.get();
         match load_guild_data(&bot_data.pool, guild_id_u64).await {
             Ok(mut guild_data) => {
+                let lang = reply_lang(&command, Some(&guild_data.settings.lang));
                 if !guild_data.is_setup {
-                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                        CreateInteractionResponseMessage::new()
-                            .content("❗ This server hasn't been setup yet!\nPlease use `/setup channels` to setup necessary channels.")
-                            .flags(InteractionResponseFlags::EPHEMERAL)
-                    )).await {
+                    if let Err(e) = command
+                        .create_response(
+                            &ctx.http,
+                            CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(tr("NOT_SETUP", &lang, &[]))
+                                    .flags(InteractionResponseFlags::EPHEMERAL),
+                            ),
+                        )
+                        .await
+                    {
                         internal_err(&ctx, &command, &e.to_string()).await;
                     }
                     return;
@@ -88,12 +228,119 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                     match top.name.as_str() {
                         "start" | "formatonly" => {
                             let formatonly = top.name == "formatonly";
+                            let only_channel = match &top.value {
+                                CommandDataOptionValue::SubCommand(sub_options) => sub_options
+                                    .iter()
+                                    .find(|o| o.name == "channel")
+                                    .and_then(|o| o.value.as_channel_id()),
+                                _ => None,
+                            };
+
+                            if let Some(channel_id) = only_channel
+                                && guild_data.channel(channel_id.get()).is_none()
+                            {
+                                if let Err(e) = command
+                                    .create_response(
+                                        &ctx.http,
+                                        CreateInteractionResponse::Message(
+                                            CreateInteractionResponseMessage::new()
+                                                .content(tr(
+                                                    "NOT_COUNTING_CHANNEL",
+                                                    &lang,
+                                                    &[("channel", &channel_id)],
+                                                ))
+                                                .flags(InteractionResponseFlags::EPHEMERAL),
+                                        ),
+                                    )
+                                    .await
+                                {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                }
+                                return;
+                            }
 
-                            if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                CreateInteractionResponseMessage::new()
-                                    .content("📝 Relog underway...\n-# If you do not see the follow up message, please make sure CountLogger has `Send Messages` permission.")
-                                    .flags(InteractionResponseFlags::EPHEMERAL)
-                            )).await {
+                            let mut problems = Vec::new();
+                            for channel in guild_data.channels.iter().filter(|c| {
+                                only_channel
+                                    .is_none_or(|id| c.ids.counting_channel_id == Some(id.get()))
+                            }) {
+                                if let (Some(count_ch_id), Some(log_ch_id)) =
+                                    (channel.ids.counting_channel_id, channel.ids.log_channel_id)
+                                {
+                                    problems.extend(
+                                        preflight(
+                                            &ctx,
+                                            guild_id,
+                                            ChannelId::new(log_ch_id),
+                                            ChannelId::new(count_ch_id),
+                                            &lang,
+                                        )
+                                        .await,
+                                    );
+                                }
+                            }
+                            problems.sort();
+                            problems.dedup();
+                            if !problems.is_empty() {
+                                if let Err(e) = command
+                                    .create_response(
+                                        &ctx.http,
+                                        CreateInteractionResponse::Message(
+                                            CreateInteractionResponseMessage::new()
+                                                .content(tr(
+                                                    "MISSING_PERMISSIONS",
+                                                    &lang,
+                                                    &[("problems", &problems.join("\n"))],
+                                                ))
+                                                .flags(InteractionResponseFlags::EPHEMERAL),
+                                        ),
+                                    )
+                                    .await
+                                {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                }
+                                return;
+                            }
+
+                            // rescanning a long history takes a while and rewrites every log
+                            let history_days: usize = guild_data
+                                .channels
+                                .iter()
+                                .filter(|c| {
+                                    only_channel.is_none_or(|id| {
+                                        c.ids.counting_channel_id == Some(id.get())
+                                    })
+                                })
+                                .map(|c| c.daily_counts.len())
+                                .sum();
+                            let result = if !formatonly && history_days >= LARGE_RELOG_DAYS {
+                                let prompt = tr("RELOG_CONFIRM", &lang, &[("days", &history_days)]);
+                                let label = tr("BUTTON_RELOG", &lang, &[]);
+                                if !confirm(&ctx, &command, &lang, prompt, label).await {
+                                    return;
+                                }
+                                command
+                                    .edit_response(
+                                        &ctx.http,
+                                        EditInteractionResponse::new()
+                                            .content(tr("RELOG_UNDERWAY", &lang, &[]))
+                                            .components(vec![]),
+                                    )
+                                    .await
+                                    .map(|_| ())
+                            } else {
+                                command
+                                    .create_response(
+                                        &ctx.http,
+                                        CreateInteractionResponse::Message(
+                                            CreateInteractionResponseMessage::new()
+                                                .content(tr("RELOG_UNDERWAY", &lang, &[]))
+                                                .flags(InteractionResponseFlags::EPHEMERAL),
+                                        ),
+                                    )
+                                    .await
+                            };
+                            if let Err(e) = result {
                                 internal_err(&ctx, &command, &e.to_string()).await;
                             }
 
@@ -101,14 +348,51 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                             let _guard = state.lock.lock().await;
                             let token = state.cancel_token.clone();
 
-                            let _ = relog_start(
+                            // settings may have changed while confirming or waiting for the lock
+                            let mut guild_data =
+                                match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                                    Ok(guild_data) => guild_data,
+                                    Err(e) => {
+                                        internal_err(&ctx, &command, &e.to_string()).await;
+                                        return;
+                                    }
+                                };
+
+                            let logged_days = |guild_data: &GuildData| -> usize {
+                                guild_data
+                                    .channels
+                                    .iter()
+                                    .map(|c| c.daily_counts.len())
+                                    .sum()
+                            };
+                            let old_days = logged_days(&guild_data);
+
+                            let result = relog_start(
                                 &ctx,
-                                &command,
+                                Some(&command),
                                 bot_data,
                                 guild_id_u64,
                                 &mut guild_data,
                                 token,
                                 formatonly,
+                                only_channel.map(|id| id.get()),
+                            )
+                            .await;
+
+                            record(
+                                &ctx,
+                                &bot_data.pool,
+                                &command,
+                                guild_data.settings.audit_channel_id,
+                                &format!("{old_days} days logged"),
+                                &format!(
+                                    "{} days logged ({}{})",
+                                    logged_days(&guild_data),
+                                    only_channel.map_or("all channels".to_string(), |id| format!(
+                                        "<#{id}>"
+                                    )),
+                                    if result.is_err() { ", failed" } else { "" }
+                                ),
                             )
                             .await;
                         }
@@ -121,7 +405,7 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                         &ctx.http,
                                         CreateInteractionResponse::Message(
                                             CreateInteractionResponseMessage::new()
-                                                .content("❌ No on-going relog session active")
+                                                .content(tr("RELOG_NONE_ACTIVE", &lang, &[]))
                                                 .flags(InteractionResponseFlags::EPHEMERAL),
                                         ),
                                     )
@@ -129,15 +413,23 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                 {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
-                                return;
                             } else {
                                 state.cancel_token.cancel();
+                                record(
+                                    &ctx,
+                                    &bot_data.pool,
+                                    &command,
+                                    guild_data.settings.audit_channel_id,
+                                    "running",
+                                    "cancelled",
+                                )
+                                .await;
                                 if let Err(e) = command
                                     .create_response(
                                         &ctx.http,
                                         CreateInteractionResponse::Message(
                                             CreateInteractionResponseMessage::new()
-                                                .content("✅ Cancelled on-going relog session")
+                                                .content(tr("RELOG_CANCELLED", &lang, &[]))
                                                 .flags(InteractionResponseFlags::EPHEMERAL),
                                         ),
                                     )
@@ -151,28 +443,114 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                         "auto" => {
                             if let CommandDataOptionValue::SubCommandGroup(group_options) =
                                 &top.value
+                                && let Some(sub) = group_options.first()
+                                && let CommandDataOptionValue::SubCommand(sub_options) = &sub.value
                             {
-                                if let Some(toggle) =
-                                    group_options.iter().find(|o| o.name == "toggle")
-                                {
-                                    if let CommandDataOptionValue::SubCommand(toggle_options) =
-                                        &toggle.value
-                                    {
-                                        if toggle_options.is_empty() {
-                                            let mut auto_relog = guild_data.settings.auto_relog;
-                                            auto_relog = !auto_relog;
-                                            guild_data.settings.auto_relog = auto_relog;
+                                match sub.name.as_str() {
+                                    "toggle" => {
+                                        let mut auto_relog = false;
+                                        let updated = update_guild_data(
+                                            &bot_data.pool,
+                                            guild_id_u64,
+                                            |data| {
+                                                data.settings.auto_relog =
+                                                    !data.settings.auto_relog;
+                                                auto_relog = data.settings.auto_relog;
+                                            },
+                                        )
+                                        .await;
+                                        guild_data = match updated {
+                                            Ok(guild_data) => guild_data,
+                                            Err(e) => {
+                                                internal_err(&ctx, &command, &e.to_string()).await;
+                                                return;
+                                            }
+                                        };
+
+                                        if let Err(e) = command
+                                            .create_response(
+                                                &ctx.http,
+                                                CreateInteractionResponse::Message(
+                                                    CreateInteractionResponseMessage::new()
+                                                        .content(tr(
+                                                            "RELOG_AUTO_TOGGLED",
+                                                            &lang,
+                                                            &[
+                                                                ("old", &!auto_relog),
+                                                                ("new", &auto_relog),
+                                                            ],
+                                                        ))
+                                                        .flags(InteractionResponseFlags::EPHEMERAL),
+                                                ),
+                                            )
+                                            .await
+                                        {
+                                            internal_err(&ctx, &command, &e.to_string()).await;
+                                        }
+
+                                        record(
+                                            &ctx,
+                                            &bot_data.pool,
+                                            &command,
+                                            guild_data.settings.audit_channel_id,
+                                            &(!auto_relog).to_string(),
+                                            &auto_relog.to_string(),
+                                        )
+                                        .await;
+                                    }
+
+                                    "interval" => {
+                                        let minutes_option = sub_options
+                                            .iter()
+                                            .find(|o| o.name == "minutes")
+                                            .and_then(|o| o.value.as_i64());
+
+                                        if let Some(minutes) = minutes_option {
+                                            let old_interval = guild_data.settings.relog_interval;
+                                            let updated = update_guild_data(
+                                                &bot_data.pool,
+                                                guild_id_u64,
+                                                |data| {
+                                                    data.settings.relog_interval = (minutes as i32)
+                                                        .clamp(
+                                                            MIN_RELOG_INTERVAL,
+                                                            MAX_RELOG_INTERVAL,
+                                                        );
+                                                },
+                                            )
+                                            .await;
+                                            guild_data = match updated {
+                                                Ok(guild_data) => guild_data,
+                                                Err(e) => {
+                                                    internal_err(&ctx, &command, &e.to_string())
+                                                        .await;
+                                                    return;
+                                                }
+                                            };
+
+                                            crate::scheduler::reschedule(guild_id_u64).await;
 
                                             if let Err(e) = command
                                                 .create_response(
                                                     &ctx.http,
                                                     CreateInteractionResponse::Message(
                                                         CreateInteractionResponseMessage::new()
-                                                            .content(format!(
-                                                                "✅ Toggled auto relog\n`{}` -> `{}`",
-                                                                !auto_relog, auto_relog
+                                                            .content(tr(
+                                                                "RELOG_INTERVAL_SET",
+                                                                &lang,
+                                                                &[
+                                                                    ("old", &old_interval),
+                                                                    (
+                                                                        "new",
+                                                                        &guild_data
+                                                                            .settings
+                                                                            .relog_interval,
+                                                                    ),
+                                                                ],
                                                             ))
-                                                            .flags(InteractionResponseFlags::EPHEMERAL),
+                                                            .flags(
+                                                                InteractionResponseFlags::EPHEMERAL,
+                                                            ),
                                                     ),
                                                 )
                                                 .await
@@ -180,14 +558,27 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                                 internal_err(&ctx, &command, &e.to_string()).await;
                                             }
 
-                                            let _ = save_guild_data(
+                                            record(
+                                                &ctx,
                                                 &bot_data.pool,
-                                                guild_id_u64,
-                                                &guild_data,
+                                                &command,
+                                                guild_data.settings.audit_channel_id,
+                                                &format!("{old_interval} min"),
+                                                &format!(
+                                                    "{} min",
+                                                    guild_data.settings.relog_interval
+                                                ),
                                             )
                                             .await;
+
+                                            log_info(&format!(
+                                                "🛠 Auto relog interval set to {} min for Guild{}",
+                                                guild_data.settings.relog_interval, guild_id_u64
+                                            ));
                                         }
                                     }
+
+                                    _ => {}
                                 }
                             }
                         }
@@ -198,9 +589,11 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     &ctx.http,
                                     CreateInteractionResponse::Message(
                                         CreateInteractionResponseMessage::new()
-                                            .content(
-                                                "❓ Available options: `start`, `cancel`, `auto`",
-                                            )
+                                            .content(tr(
+                                                "AVAILABLE_OPTIONS",
+                                                &lang,
+                                                &[("options", &"`start`, `cancel`, `auto`")],
+                                            ))
                                             .flags(InteractionResponseFlags::EPHEMERAL),
                                     ),
                                 )
@@ -214,11 +607,17 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
             }
             Err(e) => {
                 internal_err(&ctx, &command, &e.to_string()).await;
-                if let Err(e2) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                    CreateInteractionResponseMessage::new()
-                        .content("❗ Failed to fetch from Database\nPlease report the problem to developer...")
-                        .flags(InteractionResponseFlags::EPHEMERAL)
-                )).await {
+                if let Err(e2) = command
+                    .create_response(
+                        &ctx.http,
+                        CreateInteractionResponse::Message(
+                            CreateInteractionResponseMessage::new()
+                                .content(tr("DB_FETCH_FAILED", &reply_lang(&command, None), &[]))
+                                .flags(InteractionResponseFlags::EPHEMERAL),
+                        ),
+                    )
+                    .await
+                {
                     internal_err(&ctx, &command, &e2.to_string()).await;
                 }
             }
@@ -229,7 +628,7 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                 &ctx.http,
                 CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
-                        .content("❗ This command can only be used within a discord server!")
+                        .content(tr("NOT_IN_GUILD", &reply_lang(&command, None), &[]))
                         .flags(InteractionResponseFlags::EPHEMERAL),
                 ),
             )
@@ -240,64 +639,199 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
     }
 }
 
-async fn relog_start(
+#[tracing::instrument(
+    name = "relog",
+    skip_all,
+    fields(
+        guild_id = guild_id_u64,
+        relog_session = next_relog_session(),
+        formatonly = formatonly
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn relog_start(
     ctx: &Context,
-    command: &CommandInteraction,
+    command: Option<&CommandInteraction>,
     bot_data: &BotData,
     guild_id_u64: u64,
     guild_data: &mut GuildData,
     token: CancellationToken,
     formatonly: bool,
+    only_channel: Option<u64>,
 ) -> Result<(), ()> {
-    if let (Some(count_ch_id), Some(log_ch_id)) = (
-        guild_data.ids.counting_channel_id,
-        guild_data.ids.log_channel_id,
-    ) {
-        let last_msg_id = guild_data.ids.last_scanned_msg_id.clone();
-        guild_data.ids.last_scanned_msg_id = None;
+    // a full relog rebuilds daily_counts from scratch, keep a way back
+    if !formatonly
+        && let Err(e) = save_snapshot(&bot_data.pool, guild_id_u64, "relog start", guild_data).await
+    {
+        log_error(&format!(
+            "Couldn't snapshot Guild{guild_id_u64} before relog: {e}"
+        ));
+        relog_warn(
+            ctx,
+            command,
+            &tr("SNAPSHOT_FAILED", &guild_data.settings.lang, &[]),
+        )
+        .await;
+        return Err(());
+    }
 
+    let multi = guild_data.channels.len() > 1;
+
+    // one channel after another, each into its own log channel
+    for index in 0..guild_data.channels.len() {
+        let GuildData {
+            settings, channels, ..
+        } = &mut *guild_data;
+        let channel = &mut channels[index];
+        if only_channel.is_some_and(|id| channel.ids.counting_channel_id != Some(id)) {
+            continue;
+        }
+
+        relog_channel(
+            ctx,
+            command,
+            guild_id_u64,
+            settings,
+            channel,
+            token.clone(),
+            formatonly,
+            multi,
+        )
+        .await?;
+
+        store_relogged(bot_data, guild_id_u64, &guild_data.channels[index]).await;
+    }
+
+    Ok(())
+}
+
+// Saves a relogged channel into fresh data, the relog may run for minutes and must not
+// undo settings or live counts saved meanwhile
+async fn store_relogged(bot_data: &BotData, guild_id_u64: u64, relogged: &CountingChannel) {
+    let _guard = lock_guild(guild_id_u64).await;
+    let mut fresh = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+        Ok(fresh) => fresh,
+        Err(e) => {
+            log_error(&format!(
+                "❌ Cannot load data from Guild{guild_id_u64}: {e}"
+            ));
+            return;
+        }
+    };
+    let Some(channel) = fresh.channels.iter_mut().find(|c| {
+        c.ids.counting_channel_id == relogged.ids.counting_channel_id
+            && c.ids.log_channel_id == relogged.ids.log_channel_id
+    }) else {
+        return;
+    };
+
+    // a relog rebuilds the counts from scratch, only days counted live after the newest
+    // message it read are kept
+    let mode = relogged.mode;
+    let mut daily_counts = relogged.daily_counts.clone();
+    if channel.mode == mode
+        && let Some(scanned_to) = relogged.ids.last_scanned_msg_id
+        && channel.ids.last_scanned_msg_id > Some(scanned_to)
+    {
+        let scanned_day = message_day(MessageId::new(scanned_to), fresh.settings.utc)
+            .format("%Y-%m-%d")
+            .to_string();
+        for (date, count) in channel.daily_counts.range(scanned_day..) {
+            daily_counts
+                .entry(date.clone())
+                .and_modify(|v| *v = mode.furthest(*v, *count))
+                .or_insert(*count);
+        }
+    }
+    channel.mode = mode;
+    channel.daily_counts = daily_counts;
+    channel.ids.log_msg_map = relogged.ids.log_msg_map.clone();
+    channel.ids.log_helper_msg_id = relogged.ids.log_helper_msg_id;
+    if channel.ids.last_scanned_msg_id <= relogged.ids.last_scanned_msg_id {
+        channel.ids.last_scanned_msg_id = relogged.ids.last_scanned_msg_id;
+        channel.last_count = relogged.last_count;
+    }
+    let _ = save_guild_data(&bot_data.pool, guild_id_u64, &fresh).await;
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn relog_channel(
+    ctx: &Context,
+    command: Option<&CommandInteraction>,
+    guild_id_u64: u64,
+    settings: &GuildSettings,
+    channel: &mut CountingChannel,
+    token: CancellationToken,
+    formatonly: bool,
+    multi: bool,
+) -> Result<(), ()> {
+    if let (Some(count_ch_id), Some(log_ch_id)) =
+        (channel.ids.counting_channel_id, channel.ids.log_channel_id)
+    {
         let log_channel = ChannelId::new(log_ch_id);
+        if let Err(warning) = crate::threads::ensure_writable(
+            ctx,
+            GuildId::new(guild_id_u64),
+            log_channel,
+            &settings.lang,
+        )
+        .await
+        {
+            relog_warn(ctx, command, &warning).await;
+            return Err(());
+        }
+
+        let last_msg_id = channel.ids.last_scanned_msg_id;
+        channel.ids.last_scanned_msg_id = None;
 
         let progress_msg = log_channel
             .send_message(
                 &ctx.http,
-                CreateMessage::new().content("🔄 Relog in progress... this might take a while!"),
+                CreateMessage::new().content(format!(
+                    "{PROGRESS_PREFIX}... {}",
+                    tr("RELOG_PROGRESS_WAIT", &settings.lang, &[])
+                )),
             )
             .await
             .unwrap_or_default();
 
-        let relog_result: Result<
-            (BTreeMap<_, _>, Option<MessageId>),
-            Box<dyn std::error::Error + Send + Sync>,
-        > = if formatonly {
+        let relog_result: Result<Relogged, Box<dyn std::error::Error + Send + Sync>> = if formatonly
+        {
             if let Some(last) = last_msg_id {
-                Ok((guild_data.daily_counts.clone(), Some(MessageId::new(last))))
+                Ok((
+                    channel.daily_counts.clone(),
+                    Some(MessageId::new(last)),
+                    channel.last_count,
+                ))
             } else {
                 Err("No cached last message ID".into())
             }
         } else {
             get_lastmsg_day_map(
-                &ctx,
+                ctx,
                 command,
                 &progress_msg.id,
                 log_channel,
                 ChannelId::new(count_ch_id),
-                &guild_data.settings.utc,
+                &settings.utc,
+                &settings.lang,
+                channel.mode,
                 token,
             )
             .await
         };
 
         match relog_result {
-            Ok((daily_counts, last_message_id)) => {
+            Ok((daily_counts, last_message_id, last_count)) => {
                 let years: BTreeSet<String> = daily_counts
                     .keys()
                     .map(|key| key.split('-').next().unwrap().to_string())
                     .collect();
 
                 if let Some(new_last) = last_message_id {
-                    guild_data.ids.last_scanned_msg_id = Some(new_last.get());
+                    channel.ids.last_scanned_msg_id = Some(new_last.get());
                 }
+                channel.last_count = last_count;
 
                 let mut new_log_msg_map: BTreeMap<i32, BTreeMap<i64, u64>> = BTreeMap::new();
                 let mut last_year_latest_count = 0i64;
@@ -311,9 +845,11 @@ async fn relog_start(
                         .collect();
 
                     let new_log_msgs = generate_log_messages(
-                        &guild_data,
+                        settings,
+                        channel,
                         year_counts.clone(),
                         Some(last_year_latest_count),
+                        multi,
                     );
                     let mut year_map: BTreeMap<i64, u64> = BTreeMap::new();
 
@@ -322,7 +858,7 @@ async fn relog_start(
                     }
 
                     {
-                        let map: BTreeSet<u64> = guild_data
+                        let map: BTreeSet<u64> = channel
                             .ids
                             .log_msg_map
                             .clone()
@@ -335,7 +871,7 @@ async fn relog_start(
                                 .delete_message(&ctx.http, MessageId::new(id))
                                 .await
                             {
-                                internal_err(&ctx, &command, &e.to_string()).await;
+                                relog_err(ctx, command, &e.to_string()).await;
                             }
                         }
                     }
@@ -343,7 +879,7 @@ async fn relog_start(
                     for (part, new_log_msg) in new_log_msgs {
                         let embed = CreateEmbed::new().description(new_log_msg).color(0x00ffff);
 
-                        // if let Some(old_id) = guild_data
+                        // if let Some(old_id) = channel
                         //     .ids
                         //     .log_msg_map
                         //     .get(&year_i)
@@ -374,7 +910,7 @@ async fn relog_start(
                     new_log_msg_map.insert(year_i, year_map);
                 }
 
-                // let stray_log_msgs: BTreeSet<i32> = guild_data
+                // let stray_log_msgs: BTreeSet<i32> = channel
                 //     .ids
                 //     .log_msg_map
                 //     .clone()
@@ -390,26 +926,23 @@ async fn relog_start(
                 //     );
                 // }
 
-                if let Some(id) = guild_data.ids.log_helper_msg_id {
+                if let Some(id) = channel.ids.log_helper_msg_id {
                     let _ = log_channel
                         .delete_message(&ctx.http, MessageId::new(id))
                         .await;
                 }
                 {
-                    let lang1 = guild_data.settings.lang.as_str();
-                    let lang2 = guild_data.settings.lang2.as_deref();
+                    let lang1 = settings.lang.as_str();
+                    let lang2 = settings.lang2.as_deref();
 
                     if let Ok(msg) = log_channel
                         .send_message(
                             &ctx.http,
                             CreateMessage::new().content(format!(
                                 "{}{}",
-                                get_word("log_helper_msg-0", lang1, None, CharaCase::Normal,),
+                                get_word("log_helper_msg-0", lang1, None),
                                 if let Some(l2) = lang2 {
-                                    format!(
-                                        "\n\n{}",
-                                        get_word("log_helper_msg-0", l2, None, CharaCase::Normal)
-                                    )
+                                    format!("\n\n{}", get_word("log_helper_msg-0", l2, None))
                                 } else {
                                     "".to_string()
                                 }
@@ -417,40 +950,68 @@ async fn relog_start(
                         )
                         .await
                     {
-                        guild_data.ids.log_helper_msg_id = Some(msg.id.get());
+                        channel.ids.log_helper_msg_id = Some(msg.id.get());
                     }
                 }
 
                 // update state
-                guild_data.daily_counts = daily_counts;
-                guild_data.ids.log_msg_map = new_log_msg_map;
+                channel.daily_counts = daily_counts;
+                channel.ids.log_msg_map = new_log_msg_map;
 
                 log_info(&format!(
-                    "🛠 Relog Done for Guild{} ({} entries)",
+                    "🛠 Relog Done for Guild{} Channel{} ({} entries)",
                     guild_id_u64,
-                    guild_data.daily_counts.len()
+                    count_ch_id,
+                    channel.daily_counts.len()
                 ));
+                crate::webhooks::emit(
+                    guild_id_u64,
+                    &settings.webhooks,
+                    WebhookEvent::RelogCompleted,
+                    json!({
+                        "formatonly": formatonly,
+                        "channel_id": count_ch_id.to_string(),
+                        "days_logged": channel.daily_counts.len(),
+                        "total": channel.daily_counts.values().next_back(),
+                    }),
+                );
             }
             Err(e) => {
-                internal_err(&ctx, &command, &e.to_string()).await;
+                crate::metrics::discord_error(e.as_ref());
+                relog_err(ctx, command, &e.to_string()).await;
+                crate::webhooks::emit(
+                    guild_id_u64,
+                    &settings.webhooks,
+                    WebhookEvent::RelogFailed,
+                    json!({
+                        "formatonly": formatonly,
+                        "channel_id": count_ch_id.to_string(),
+                        "error": e.to_string(),
+                    }),
+                );
 
                 if formatonly {
                     let _ = log_channel
                         .edit_message(
-                        &ctx.http,
-                        progress_msg.id,
-                        EditMessage::new().content(
-                            "❌ Insufficient data. Please do normal relog to acquire new data in the process\n-# This message will delete automatically in 10 seconds",
-                        ),
-                    ).await;
+                            &ctx.http,
+                            progress_msg.id,
+                            EditMessage::new().content(tr(
+                                "RELOG_INSUFFICIENT_DATA",
+                                &settings.lang,
+                                &[],
+                            )),
+                        )
+                        .await;
                 } else {
                     let _ = log_channel
                         .edit_message(
                             &ctx.http,
                             progress_msg.id,
-                            EditMessage::new().content(
-                                "❗ Relog Session Interrupted!\n-# This message will delete automatically in 10 seconds",
-                            ),
+                            EditMessage::new().content(tr(
+                                "RELOG_INTERRUPTED",
+                                &settings.lang,
+                                &[],
+                            )),
                         )
                         .await;
                 }
@@ -462,15 +1023,11 @@ async fn relog_start(
             }
         }
 
-        let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
-
         let _ = log_channel
             .edit_message(
                 &ctx.http,
                 progress_msg.id,
-                EditMessage::new().content(
-                    "✅ Relog Done!\n-# This message will delete automatically in 10 seconds",
-                ),
+                EditMessage::new().content(tr("RELOG_DONE", &settings.lang, &[])),
             )
             .await;
 
@@ -482,170 +1039,459 @@ async fn relog_start(
     Ok(())
 }
 
-pub async fn log_daily_counts(ctx: Context, bot_data: std::sync::Arc<BotData>) {
-    let mut interval = interval(chrono::Duration::minutes(5).to_std().unwrap());
-    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+pub type RelogError = Box<dyn std::error::Error + Send + Sync>;
 
-    loop {
-        interval.tick().await;
-
-        match load_all_data(&bot_data.pool).await {
-            Ok(guilds) => {
-                for (guild_id_u64, mut guild_data) in guilds.0 {
-                    let state = get_relog_state(guild_id_u64).await;
-                    if state.lock.try_lock().is_err() || !guild_data.settings.auto_relog {
-                        continue;
-                    }
+// Relogs started without an interaction (admin API) can only report to the log
+async fn relog_err(ctx: &Context, command: Option<&CommandInteraction>, err: &str) {
+    match command {
+        Some(command) => internal_err(ctx, command, err).await,
+        None => log_error(err),
+    }
+}
 
-                    if let Some(count_ch_id) = guild_data.ids.counting_channel_id {
-                        if let Some(log_ch_id) = guild_data.ids.log_channel_id {
-                            let count_channel = ChannelId::new(count_ch_id);
-                            let log_channel = ChannelId::new(log_ch_id);
+// Problems the server can act on, already logged by the caller, reach the user as they are
+async fn relog_warn(ctx: &Context, command: Option<&CommandInteraction>, warning: &str) {
+    let Some(command) = command else {
+        return;
+    };
+    if let Err(e) = command
+        .create_followup(
+            &ctx.http,
+            CreateInteractionResponseFollowup::new()
+                .content(warning)
+                .ephemeral(true),
+        )
+        .await
+    {
+        internal_err(ctx, command, &e.to_string()).await;
+    }
+}
 
-                            match fetch_new_daily_counts(
-                                &ctx.http,
-                                count_channel,
-                                &guild_data.settings.utc,
-                                guild_data.ids.last_scanned_msg_id.map(MessageId::new),
-                            )
-                            .await
-                            {
-                                Ok((new_counts, last_seen)) => {
-                                    // merge into existing counts
-                                    for (date, count) in new_counts {
-                                        guild_data
-                                            .daily_counts
-                                            .entry(date)
-                                            .and_modify(|v| *v = (*v).max(count))
-                                            .or_insert(count);
-                                    }
-                                    if let Some(new_last) = last_seen {
-                                        guild_data.ids.last_scanned_msg_id = Some(new_last.get());
-                                    }
+// Fetch new counts for every counting channel of a guild and refresh their current
+// year logs (plus any `extra_years` whose counts changed since they were logged),
+// `force_logs` refreshes them even with auto relog turned off,
+// returns how many new counting messages were read, `None` if a relog is already running
+#[tracing::instrument(
+    name = "auto_relog",
+    skip_all,
+    fields(guild_id = guild_id_u64, relog_session = next_relog_session())
+)]
+pub async fn auto_relog_guild(
+    ctx: &Context,
+    bot_data: &BotData,
+    guild_id_u64: u64,
+    extra_years: &BTreeSet<i32>,
+    force_logs: bool,
+) -> Result<Option<usize>, RelogError> {
+    let state = get_relog_state(guild_id_u64).await;
+    let Ok(_guard) = state.lock.try_lock() else {
+        return Ok(None);
+    };
 
-                                    // update ONLY current year logs
-                                    let offset = FixedOffset::east_opt(
-                                        guild_data.settings.utc as i32 * 3600,
-                                    )
-                                    .unwrap();
-                                    let year_now = Utc::now().with_timezone(&offset).year();
-
-                                    let year_counts: BTreeMap<String, i64> = guild_data
-                                        .daily_counts
-                                        .iter()
-                                        .filter(|(k, _)| k.starts_with(&year_now.to_string()))
-                                        .map(|(k, v)| (k.clone(), *v))
-                                        .collect();
-
-                                    let new_log_msgs =
-                                        generate_log_messages(&guild_data, year_counts, None);
-
-                                    let mut year_map = guild_data
-                                        .ids
-                                        .log_msg_map
-                                        .remove(&year_now)
-                                        .unwrap_or_default();
-
-                                    let mut is_new_log_msg = false;
-
-                                    for (part, new_msg) in new_log_msgs.clone() {
-                                        let embed =
-                                            CreateEmbed::new().description(new_msg).color(0x00ffff);
-
-                                        if let Some(&old_id) = year_map.get(&part) {
-                                            let _ = log_channel
-                                                .edit_message(
-                                                    &ctx.http,
-                                                    MessageId::new(old_id),
-                                                    EditMessage::new().embed(embed.clone()),
-                                                )
-                                                .await;
-                                        } else if let Ok(new_msg) = log_channel
-                                            .send_message(
-                                                &ctx.http,
-                                                CreateMessage::new().embed(embed.clone()),
-                                            )
-                                            .await
-                                        {
-                                            is_new_log_msg = true;
-                                            year_map.insert(part, new_msg.id.get());
-                                        }
-                                    }
+    let mut guild_data = load_guild_data(&bot_data.pool, guild_id_u64).await?;
+
+    // counts are still kept up to date, but logs stay untouched
+    let refresh_logs = guild_data.settings.auto_relog || force_logs;
+    let multi = guild_data.channels.len() > 1;
+
+    // a broken channel shouldn't hold back the others, its error is reported once all ran
+    let mut scanned = 0;
+    let mut failure = None;
+    let GuildData {
+        settings, channels, ..
+    } = &mut guild_data;
+    for channel in channels.iter_mut() {
+        match auto_relog_channel(
+            ctx,
+            guild_id_u64,
+            settings,
+            channel,
+            extra_years,
+            refresh_logs,
+            multi,
+        )
+        .await
+        {
+            Ok(channel_scanned) => scanned += channel_scanned,
+            Err(e) => {
+                failure.get_or_insert(e);
+            }
+        }
+    }
 
-                                    if is_new_log_msg {
-                                        if let Some(id) = guild_data.ids.log_helper_msg_id {
-                                            let _ = log_channel
-                                                .delete_message(&ctx.http, MessageId::new(id));
-                                        }
-                                        {
-                                            let lang1 = guild_data.settings.lang.as_str();
-                                            let lang2 = guild_data.settings.lang2.as_deref();
+    // the scan took a while, settings and live counts saved meanwhile must survive
+    {
+        let _guard = lock_guild(guild_id_u64).await;
+        let mut fresh = load_guild_data(&bot_data.pool, guild_id_u64).await?;
+        merge_scanned(&mut fresh, guild_data.channels);
+        save_guild_data(&bot_data.pool, guild_id_u64, &fresh).await?;
+    }
 
-                                            if let Ok(msg) = log_channel
-                                                .send_message(
-                                                    &ctx.http,
-                                                    CreateMessage::new().content(get_word(
-                                                        "log_helper_msg-0",
-                                                        lang1,
-                                                        lang2,
-                                                        CharaCase::Normal,
-                                                    )),
-                                                )
-                                                .await
-                                            {
-                                                guild_data.ids.log_helper_msg_id =
-                                                    Some(msg.id.get());
-                                            }
-                                        }
-                                    }
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(Some(scanned)),
+    }
+}
 
-                                    // cleanup: if year_map had leftover parts not regenerated
-                                    let valid_parts: Vec<i64> =
-                                        new_log_msgs.keys().cloned().collect();
-                                    year_map.retain(|part, _| valid_parts.contains(part));
+// Carries what a relog scanned and logged into freshly loaded data, channels that were
+// reconfigured or removed during the scan are left alone
+fn merge_scanned(fresh: &mut GuildData, scanned: Vec<CountingChannel>) {
+    for scanned in scanned {
+        let Some(channel) = fresh.channels.iter_mut().find(|c| {
+            c.ids.counting_channel_id == scanned.ids.counting_channel_id
+                && c.ids.log_channel_id == scanned.ids.log_channel_id
+                && c.mode == scanned.mode
+        }) else {
+            continue;
+        };
 
-                                    guild_data.ids.log_msg_map.insert(year_now, year_map);
+        let mode = channel.mode;
+        for (date, count) in scanned.daily_counts {
+            channel
+                .daily_counts
+                .entry(date)
+                .and_modify(|v| *v = mode.furthest(*v, count))
+                .or_insert(count);
+        }
+        // live counts past the scan know the later last count
+        if channel.ids.last_scanned_msg_id <= scanned.ids.last_scanned_msg_id {
+            channel.ids.last_scanned_msg_id = scanned.ids.last_scanned_msg_id;
+            channel.last_count = scanned.last_count;
+        }
+        channel.ids.log_msg_map = scanned.ids.log_msg_map;
+        channel.ids.log_helper_msg_id = scanned.ids.log_helper_msg_id;
+    }
+}
 
-                                    let _ =
-                                        save_guild_data(&bot_data.pool, guild_id_u64, &guild_data)
-                                            .await;
-                                }
-                                Err(e) => {
-                                    log_error(&format!(
-                                        "Failed fetching new counts for Guild{}: {}",
-                                        guild_id_u64, e
-                                    ));
-                                }
-                            }
-                        }
-                    }
+async fn auto_relog_channel(
+    ctx: &Context,
+    guild_id_u64: u64,
+    settings: &GuildSettings,
+    channel: &mut CountingChannel,
+    extra_years: &BTreeSet<i32>,
+    refresh_logs: bool,
+    multi: bool,
+) -> Result<usize, RelogError> {
+    let (Some(count_ch_id), Some(log_ch_id)) =
+        (channel.ids.counting_channel_id, channel.ids.log_channel_id)
+    else {
+        return Ok(0);
+    };
+    let count_channel = ChannelId::new(count_ch_id);
+    let log_channel = ChannelId::new(log_ch_id);
+
+    let (new_counts, last_seen, scanned) = fetch_new_daily_counts(
+        &ctx.http,
+        count_channel,
+        &settings.utc,
+        channel.mode,
+        channel.ids.last_scanned_msg_id.map(MessageId::new),
+        &mut channel.last_count,
+    )
+    .await?;
+
+    // merge into existing counts
+    let mode = channel.mode;
+    for (date, count) in new_counts {
+        channel
+            .daily_counts
+            .entry(date)
+            .and_modify(|v| *v = mode.furthest(*v, count))
+            .or_insert(count);
+    }
+    if let Some(new_last) = last_seen {
+        channel.ids.last_scanned_msg_id = Some(new_last.get());
+    }
+
+    if !refresh_logs {
+        return Ok(scanned);
+    }
+    // background relogs only report to the log
+    crate::threads::ensure_writable(
+        ctx,
+        GuildId::new(guild_id_u64),
+        log_channel,
+        crate::i18n::DEFAULT_LANG,
+    )
+    .await?;
+
+    // update ONLY current year logs (and years marked for refresh)
+    let offset = FixedOffset::east_opt(settings.utc as i32 * 3600).unwrap();
+    let year_now = Utc::now().with_timezone(&offset).year();
+
+    let mut years = extra_years.clone();
+    years.insert(year_now);
+
+    let mut is_new_log_msg = false;
+    for year in years {
+        is_new_log_msg |=
+            refresh_year_logs(ctx, settings, channel, log_channel, year, multi).await?;
+    }
+
+    if is_new_log_msg {
+        if let Some(id) = channel.ids.log_helper_msg_id {
+            let _ = log_channel
+                .delete_message(&ctx.http, MessageId::new(id))
+                .await;
+        }
+        {
+            let lang1 = settings.lang.as_str();
+            let lang2 = settings.lang2.as_deref();
+
+            if let Ok(msg) = log_channel
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new().content(get_word("log_helper_msg-0", lang1, lang2)),
+                )
+                .await
+            {
+                channel.ids.log_helper_msg_id = Some(msg.id.get());
+            }
+        }
+    }
+
+    Ok(scanned)
+}
+
+// Edit (or send) the log messages of a single year, returns true if a new message was sent
+async fn refresh_year_logs(
+    ctx: &Context,
+    settings: &GuildSettings,
+    channel: &mut CountingChannel,
+    log_channel: ChannelId,
+    year: i32,
+    multi: bool,
+) -> Result<bool, RelogError> {
+    let year_prefix = format!("{year}-");
+    let year_counts: BTreeMap<String, i64> = channel
+        .daily_counts
+        .iter()
+        .filter(|(k, _)| k.starts_with(&year_prefix))
+        .map(|(k, v)| (k.clone(), *v))
+        .collect();
+
+    // last count of the previous year, so the first increment matches a full relog
+    let last_year_latest_count = channel
+        .daily_counts
+        .range(..year_prefix.clone())
+        .next_back()
+        .map(|(_, v)| *v);
+
+    let new_log_msgs = generate_log_messages(
+        settings,
+        channel,
+        year_counts,
+        last_year_latest_count,
+        multi,
+    );
+
+    let mut year_map = channel.ids.log_msg_map.remove(&year).unwrap_or_default();
+
+    let mut is_new_log_msg = false;
+
+    for (part, new_msg) in new_log_msgs.clone() {
+        let embed = CreateEmbed::new().description(new_msg).color(0x00ffff);
+
+        let edited = match year_map.get(&part) {
+            Some(&old_id) => log_channel
+                .edit_message(
+                    &ctx.http,
+                    MessageId::new(old_id),
+                    EditMessage::new().embed(embed.clone()),
+                )
+                .await
+                .inspect_err(|e| crate::metrics::discord_error(e))
+                .is_ok(),
+            None => false,
+        };
+
+        // fallback: create new (a failing send here means the channel itself is unusable)
+        if !edited {
+            let new_msg = log_channel
+                .send_message(&ctx.http, CreateMessage::new().embed(embed.clone()))
+                .await?;
+            is_new_log_msg = true;
+            year_map.insert(part, new_msg.id.get());
+        }
+    }
+
+    // cleanup: delete leftover parts that weren't regenerated
+    let stray_parts: Vec<i64> = year_map
+        .keys()
+        .filter(|part| !new_log_msgs.contains_key(part))
+        .copied()
+        .collect();
+    for part in stray_parts {
+        if let Some(id) = year_map.remove(&part) {
+            let _ = log_channel
+                .delete_message(&ctx.http, MessageId::new(id))
+                .await;
+        }
+    }
+
+    if !year_map.is_empty() {
+        channel.ids.log_msg_map.insert(year, year_map);
+    }
+
+    Ok(is_new_log_msg)
+}
+
+// Discord epoch (2015-01-01) in unix milliseconds
+const DISCORD_EPOCH_MS: i64 = 1_420_070_400_000;
+
+/// The lowest message id Discord could hand out at `time`, messages `before` it were sent earlier
+pub fn snowflake_at(time: DateTime<Utc>) -> MessageId {
+    MessageId::new(((time.timestamp_millis() - DISCORD_EPOCH_MS).max(1) as u64) << 22)
+}
+
+fn utc_offset(utc: i8) -> FixedOffset {
+    FixedOffset::east_opt(utc as i32 * 3600).unwrap_or(FixedOffset::east_opt(0).unwrap())
+}
+
+/// The local date (in the guild's timezone) a message was sent on
+pub fn message_day(message_id: MessageId, utc: i8) -> NaiveDate {
+    message_id
+        .created_at()
+        .with_timezone(&utc_offset(utc))
+        .date_naive()
+}
+
+/// Recount the given days of the counting channel after messages were edited or
+/// deleted, returns the years whose counts changed
+pub async fn recount_days(
+    http: &Http,
+    bot_data: &BotData,
+    guild_id_u64: u64,
+    channel_id: ChannelId,
+    days: &BTreeSet<NaiveDate>,
+) -> Result<BTreeSet<i32>, RelogError> {
+    let state = get_relog_state(guild_id_u64).await;
+    let _guard = state.lock.lock().await;
+
+    let guild_data = load_guild_data(&bot_data.pool, guild_id_u64).await?;
+    let utc = guild_data.settings.utc;
+    let Some(channel) = guild_data.channel(channel_id.get()) else {
+        return Ok(BTreeSet::new());
+    };
+
+    let mut changes = BTreeMap::new();
+    for day in days {
+        let key = day.format("%Y-%m-%d").to_string();
+        let old = channel.daily_counts.get(&key).copied();
+        let new = recount_day(http, channel_id, utc, channel.mode, *day).await?;
+        if old != new {
+            changes.insert(*day, new);
+        }
+    }
+    if changes.is_empty() {
+        return Ok(BTreeSet::new());
+    }
+
+    // applied to fresh data, live counts may have been saved while recounting
+    let _guard = lock_guild(guild_id_u64).await;
+    let mut guild_data = load_guild_data(&bot_data.pool, guild_id_u64).await?;
+    let Some(channel) = guild_data.channel_mut(channel_id.get()) else {
+        return Ok(BTreeSet::new());
+    };
+    let mut changed_years = BTreeSet::new();
+    for (day, new) in changes {
+        let key = day.format("%Y-%m-%d").to_string();
+        changed_years.insert(day.year());
+        match new {
+            Some(count) => channel.daily_counts.insert(key, count),
+            None => channel.daily_counts.remove(&key),
+        };
+    }
+    save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await?;
+
+    Ok(changed_years)
+}
+
+// Highest valid count of a single day, validated the same way as the scanners
+async fn recount_day(
+    http: &Http,
+    channel_id: ChannelId,
+    utc: i8,
+    mode: CountMode,
+    day: NaiveDate,
+) -> serenity::Result<Option<i64>> {
+    let day_start = day
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_local_timezone(utc_offset(utc))
+        .unwrap()
+        .with_timezone(&Utc);
+    let day_end = day_start + chrono::Duration::days(1);
+
+    // seed with the last count before this day (messages come newest first)
+    let mut last_num = channel_id
+        .messages(
+            http,
+            GetMessages::new()
+                .before(snowflake_at(day_start))
+                .limit(100),
+        )
+        .await?
+        .iter()
+        .filter(|m| !m.author.bot)
+        .find_map(|m| mode.parse(&m.content));
+
+    let mut day_max: Option<i64> = None;
+    let mut after = snowflake_at(day_start);
+
+    'pages: loop {
+        let mut page_msgs = channel_id
+            .messages(http, GetMessages::new().after(after).limit(100))
+            .await?;
+        if page_msgs.is_empty() {
+            break;
+        }
+        page_msgs.reverse();
+
+        for msg in &page_msgs {
+            if *msg.timestamp >= day_end {
+                break 'pages;
+            }
+            if msg.author.bot {
+                continue;
+            }
+            if let Some(num) = mode.parse(&msg.content) {
+                if last_num.is_none_or(|last| mode.is_valid(last, num)) {
+                    day_max = Some(day_max.map_or(num, |max| mode.furthest(max, num)));
                 }
+                last_num = mode.advance(last_num, num);
             }
-            Err(_) => {}
         }
+
+        if page_msgs.len() < 100 {
+            break;
+        }
+        after = page_msgs.last().unwrap().id;
     }
+
+    Ok(day_max)
 }
 
-// Fetch ONLY messages after last_scanned_msg_id
+// Fetch ONLY messages after last_scanned_msg_id, `last_num` carries the previous
+// number in and the last one read out
 async fn fetch_new_daily_counts(
     http: &Http,
     channel_id: ChannelId,
     utc: &i8,
+    mode: CountMode,
     last_scanned: Option<MessageId>,
-) -> serenity::Result<(BTreeMap<String, i64>, Option<MessageId>)> {
+    last_num: &mut Option<i64>,
+) -> serenity::Result<(BTreeMap<String, i64>, Option<MessageId>, usize)> {
     let mut daily_counts: BTreeMap<String, i64> = BTreeMap::new();
     let mut last_seen: Option<MessageId> = None;
-    let mut last_num = {
-        let mut n = 0i64;
-        if let Some(last_id) = last_scanned.clone() {
-            if let Ok(msg) = channel_id.message(http, last_id).await {
-                if let Ok(num) = msg.content.parse::<i64>() {
-                    n = num;
-                }
-            }
-        }
-        n
-    };
+    let mut scanned = 0usize;
+    if last_num.is_none()
+        && let Some(last_id) = last_scanned
+        && let Ok(msg) = channel_id.message(http, last_id).await
+        && let Some(num) = mode.parse(&msg.content)
+    {
+        *last_num = Some(num);
+    }
 
     let mut get_message = GetMessages::new().limit(100);
     if let Some(last_id) = last_scanned {
@@ -653,7 +1499,7 @@ async fn fetch_new_daily_counts(
     }
 
     loop {
-        let msgs = channel_id.messages(http, get_message.clone()).await?;
+        let msgs = channel_id.messages(http, get_message).await?;
         if msgs.is_empty() {
             break;
         }
@@ -661,26 +1507,9 @@ async fn fetch_new_daily_counts(
         let mut page_msgs = msgs;
         page_msgs.reverse();
 
-        for msg in &page_msgs {
-            if msg.author.bot {
-                continue;
-            }
-            if let Ok(num) = msg.content.parse::<i64>() {
-                if let Some(offset) =
-                    FixedOffset::east_opt(utc.clone().clamp(-14, 12) as i32 * 3600)
-                {
-                    let dt: DateTime<FixedOffset> = msg.timestamp.with_timezone(&offset);
-                    let key = dt.date_naive().format("%Y-%m-%d").to_string();
-                    if last_num == 0 || is_valid_num(last_num, num) {
-                        daily_counts
-                            .entry(key)
-                            .and_modify(|v| *v = (*v).max(num))
-                            .or_insert(num);
-                    }
-                    last_num = num;
-                }
-            }
-        }
+        let (read, invalid) = tally_page(&page_msgs, *utc, mode, last_num, &mut daily_counts);
+        crate::metrics::invalid_counts("scan", invalid as u64);
+        scanned += read;
 
         last_seen = page_msgs.last().map(|m| m.id);
 
@@ -693,24 +1522,63 @@ async fn fetch_new_daily_counts(
             .after(page_msgs.last().unwrap().id);
     }
 
-    Ok((daily_counts, last_seen))
+    crate::metrics::counting_messages("scan", scanned as u64);
+    Ok((daily_counts, last_seen, scanned))
+}
+
+// Tallies messages (oldest first) into the furthest valid count of each day, `last_num`
+// carries the previous count in and the last one read out. Returns how many counts were
+// read and how many of them were invalid
+fn tally_page(
+    msgs: &[Message],
+    utc: i8,
+    mode: CountMode,
+    last_num: &mut Option<i64>,
+    daily_counts: &mut BTreeMap<String, i64>,
+) -> (usize, usize) {
+    let mut read = 0usize;
+    let mut invalid = 0usize;
+    for msg in msgs {
+        if msg.author.bot {
+            continue;
+        }
+        let Some(num) = mode.parse(&msg.content) else {
+            continue;
+        };
+        if last_num.is_none_or(|last| mode.is_valid(last, num)) {
+            daily_counts
+                .entry(message_day(msg.id, utc).format("%Y-%m-%d").to_string())
+                .and_modify(|v| *v = mode.furthest(*v, num))
+                .or_insert(num);
+        } else {
+            invalid += 1;
+        }
+        *last_num = mode.advance(*last_num, num);
+        read += 1;
+    }
+    (read, invalid)
 }
 
+// Counts by day, the newest message read and the last count
+type Relogged = (BTreeMap<String, i64>, Option<MessageId>, Option<i64>);
+
+// Reads the whole counting channel oldest to newest, so every count is checked against
+// the one sent before it
+#[allow(clippy::too_many_arguments)]
 async fn get_lastmsg_day_map(
     ctx: &Context,
-    command: &CommandInteraction,
+    command: Option<&CommandInteraction>,
     progress_msg: &MessageId,
     log_channel_id: ChannelId,
     count_channel_id: ChannelId,
     utc: &i8,
+    lang: &str,
+    mode: CountMode,
     token: CancellationToken,
-) -> serenity::Result<
-    (BTreeMap<String, i64>, Option<MessageId>),
-    Box<dyn std::error::Error + Send + Sync>,
-> {
+) -> serenity::Result<Relogged, Box<dyn std::error::Error + Send + Sync>> {
     let mut daily_counts: BTreeMap<String, i64> = BTreeMap::new();
     let mut last_message_id: Option<MessageId> = None;
-    let mut last_num = 0i64;
+    let mut last_num: Option<i64> = None;
 
     let mut last_update = Instant::now();
     let unix_time = SystemTime::now()
@@ -719,15 +1587,26 @@ async fn get_lastmsg_day_map(
         .as_secs();
     let start_timestamp = format!("<t:{}:R>", unix_time);
 
-    let mut total_count = 0i64;
-    let mut total_invalid_detected = 0i64;
+    // the newest count is where the progress heads to
+    let total_count = count_channel_id
+        .messages(&ctx.http, GetMessages::new().limit(100))
+        .await?
+        .iter()
+        .filter(|m| !m.author.bot)
+        .find_map(|m| mode.parse(&m.content))
+        .unwrap_or_default();
+    let mut total_invalid_detected = 0usize;
 
     loop {
-        let mut get_message = GetMessages::new().limit(100);
-        if let Some(last_id) = last_message_id {
-            get_message = get_message.before(last_id);
+        if token.is_cancelled() {
+            return Err("CancelledToken".into());
         }
-        let msgs = count_channel_id.messages(&ctx.http, get_message).await?;
+
+        // the oldest messages come first after the lowest possible id
+        let after = last_message_id.unwrap_or(MessageId::new(1));
+        let msgs = count_channel_id
+            .messages(&ctx.http, GetMessages::new().after(after).limit(100))
+            .await?;
         if msgs.is_empty() {
             break;
         }
@@ -735,94 +1614,100 @@ async fn get_lastmsg_day_map(
         let mut page_msgs = msgs;
         page_msgs.reverse();
 
-        for msg in &page_msgs {
-            if token.is_cancelled() {
-                return Err("CancelledToken".into());
-            }
-
-            if msg.author.bot {
-                continue;
-            }
-            if let Ok(num) = msg.content.parse::<i64>() {
-                if total_count == 0 {
-                    total_count = num
-                };
-                if let Some(offset) =
-                    FixedOffset::east_opt(utc.clone().clamp(-14, 12) as i32 * 3600)
-                {
-                    if last_update.elapsed() >= Duration::from_secs(10) {
-                        let unix_time = SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs();
-                        let update_timestamp = format!("<t:{}:R>", unix_time);
-
-                        if let Err(e) = log_channel_id
-                            .edit_message(
-                                &ctx.http,
-                                progress_msg,
-                                EditMessage::new().content(format!(
-                                    "🔄 Relog in progress...\n📊 Read Counts Left: `{}/{}`{}\n-# Started {}\n-# Last Update {}",
-                                    &num,
-                                    &total_count,
-                                    if total_invalid_detected > 0 {
-                                        format!("\nInvalid Counts Detected: `{}`", total_invalid_detected)
+        let (read, invalid) = tally_page(&page_msgs, *utc, mode, &mut last_num, &mut daily_counts);
+        crate::metrics::counting_messages("relog", read as u64);
+        crate::metrics::invalid_counts("relog", invalid as u64);
+        total_invalid_detected += invalid;
+
+        if last_update.elapsed() >= Duration::from_secs(10) {
+            let unix_time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let update_timestamp = format!("<t:{}:R>", unix_time);
+            let left = last_num.map_or(total_count, |num| total_count - num).abs();
+
+            if let Err(e) = log_channel_id
+                .edit_message(
+                    &ctx.http,
+                    progress_msg,
+                    EditMessage::new().content(format!(
+                        "{PROGRESS_PREFIX}...\n{}",
+                        tr(
+                            "RELOG_PROGRESS",
+                            lang,
+                            &[
+                                ("left", &left),
+                                ("total", &total_count),
+                                (
+                                    "invalid",
+                                    &if total_invalid_detected > 0 {
+                                        tr(
+                                            "RELOG_PROGRESS_INVALID",
+                                            lang,
+                                            &[("invalid", &total_invalid_detected)],
+                                        )
                                     } else {
                                         "".to_string()
                                     },
-                                    start_timestamp,
-                                    update_timestamp
-                                )),
-                            )
-                            .await {
-                                internal_err(&ctx, &command, &e.to_string()).await;
-                            }
-
-                        last_update = Instant::now();
-                    }
-
-                    let dt: DateTime<FixedOffset> = msg.timestamp.with_timezone(&offset);
-                    let key = dt.date_naive().format("%Y-%m-%d").to_string();
-                    if last_num != 0 && !is_valid_num(last_num, num) {
-                        total_invalid_detected += 1;
-                    } else {
-                        daily_counts
-                            .entry(key)
-                            .and_modify(|v| *v = (*v).max(num))
-                            .or_insert(num);
-                    }
-                    last_num = num;
-                }
+                                ),
+                                ("started", &start_timestamp),
+                                ("updated", &update_timestamp),
+                            ],
+                        )
+                    )),
+                )
+                .await
+            {
+                relog_err(ctx, command, &e.to_string()).await;
             }
+
+            last_update = Instant::now();
         }
 
         // guard against infinite loop
-        let new_last = page_msgs.first().map(|m| m.id);
+        let new_last = page_msgs.last().map(|m| m.id);
         if new_last == last_message_id {
             break;
         }
         last_message_id = new_last;
+        if page_msgs.len() < 100 {
+            break;
+        }
     }
 
-    Ok((daily_counts, last_message_id))
+    Ok((daily_counts, last_message_id, last_num))
 }
 
+// `multi` marks guilds with several counting channels, their logs name the channel they belong to
 fn generate_log_messages(
-    guild_data: &GuildData,
+    settings: &GuildSettings,
+    channel: &CountingChannel,
     counts: BTreeMap<String, i64>,
     offset_count: Option<i64>,
+    multi: bool,
 ) -> BTreeMap<i64, String> {
-    let lang1 = guild_data.settings.lang.as_str();
-    let lang2 = guild_data.settings.lang2.as_deref();
-    let utc = &guild_data.settings.utc;
+    let lang1 = settings.lang.as_str();
+    let lang2 = settings.lang2.as_deref();
+    let utc = &settings.utc;
+    let mode = channel.mode;
+
+    let channel_line = match channel.ids.counting_channel_id {
+        Some(id) if multi || mode != CountMode::Normal => format!(
+            "<#{id}>{}\n",
+            if mode == CountMode::Normal {
+                "".to_string()
+            } else {
+                format!(" `{}`", mode.name())
+            }
+        ),
+        _ => "".to_string(),
+    };
 
     let mut messages: BTreeMap<i64, String> = BTreeMap::default();
     let mut msg_lines: Vec<String> = Vec::new();
     let mut line_count = 0usize;
-    let mut prev_count = match offset_count {
-        Some(count) => count,
-        None => 0i64,
-    };
+    let mut prev_count = offset_count.unwrap_or_default();
     let mut part = 1i64;
 
     if counts.is_empty() {
@@ -844,17 +1729,17 @@ fn generate_log_messages(
         let increment = count - prev_count;
         prev_count = count;
 
-        let line = format!("`{m}-{d}` {count} (+{increment})");
+        let line = format!("`{m}-{d}` {} ({increment:+})", mode.format(count));
         msg_lines.push(line);
         line_count += 1;
 
-        if line_count % 10 == 0 {
+        if line_count.is_multiple_of(10) {
             msg_lines.push(format!("-# -{line_count}-"));
         }
 
         let is_last = last_date.as_ref().map(|s| s == &date).unwrap_or(false);
 
-        if is_last && line_count % 10 != 0 {
+        if is_last && !line_count.is_multiple_of(10) {
             msg_lines.push(format!("-# -{line_count}-"));
         }
 
@@ -862,17 +1747,19 @@ fn generate_log_messages(
         will result in unmarked-down message (only if not an embed)
         Don't ask me why discord is like this*/
         if line_count >= 100 || is_last {
-            // "## 📊 Count Log\n## `Year {}`\n`date : sum (5 min update)`\n"
+            // "## 📊 Count Log\n## `Year {}`\n`date : sum ({} min update)`\n"
             let header = format!(
-                "## 📊 {}\n## `{} {} ({})`\n`{} (UTC {}) : {}`\n`({})`\n",
-                get_word("Count Log", lang1, None, CharaCase::Normal),
-                get_word("Year", lang1, lang2, CharaCase::Normal),
-                y.to_string(),
-                part.to_string(),
-                get_word("Date", lang1, lang2, CharaCase::Normal),
+                "## 📊 {}\n## `{} {} ({})`\n{}`{} (UTC {}) : {}`\n`({})`\n",
+                get_word("Count Log", lang1, None),
+                get_word("Year", lang1, lang2),
+                y,
+                part,
+                channel_line,
+                get_word("Date", lang1, lang2),
                 get_utc_format(utc),
-                get_word("Sum", lang1, lang2, CharaCase::Normal),
-                get_word("5 minutes change", lang1, lang2, CharaCase::Normal),
+                get_word("Sum", lang1, lang2),
+                get_word("minutes update", lang1, lang2)
+                    .replace("{}", &settings.relog_interval.to_string()),
             );
             messages.insert(part, format!("{}{}", header, msg_lines.join("\n")));
             msg_lines.clear();
@@ -884,6 +1771,71 @@ fn generate_log_messages(
     messages
 }
 
-fn is_valid_num(pre: i64, post: i64) -> bool {
-    pre + 1 == post
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // counts `from..=to` a minute apart, oldest first like the pages the relog reads
+    fn page(from: i64, to: i64) -> Vec<Message> {
+        (from..=to)
+            .map(|num| {
+                let mut msg = Message::default();
+                let sent_at = DateTime::from_timestamp(1_700_000_000 + num * 60, 0).unwrap();
+                msg.id = snowflake_at(sent_at);
+                msg.content = num.to_string();
+                msg
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tally_keeps_every_page() {
+        let mut last_num = None;
+        let mut daily_counts = BTreeMap::new();
+        let mut invalid = 0;
+        for page in [page(801, 900), page(901, 1000)] {
+            invalid += tally_page(
+                &page,
+                0,
+                CountMode::Normal,
+                &mut last_num,
+                &mut daily_counts,
+            )
+            .1;
+        }
+
+        assert_eq!(invalid, 0);
+        assert_eq!(last_num, Some(1000));
+        assert_eq!(daily_counts.values().copied().max(), Some(1000));
+        assert_eq!(
+            daily_counts.values().copied().min().map(|c| c >= 801),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn tally_checks_counts_across_pages() {
+        let mut last_num = None;
+        let mut daily_counts = BTreeMap::new();
+        tally_page(
+            &page(1, 10),
+            0,
+            CountMode::Normal,
+            &mut last_num,
+            &mut daily_counts,
+        );
+
+        // a wrong number doesn't move the count, the right one still follows it
+        let mut wrong = page(12, 12);
+        wrong.extend(page(11, 11));
+        let (read, invalid) = tally_page(
+            &wrong,
+            0,
+            CountMode::Normal,
+            &mut last_num,
+            &mut daily_counts,
+        );
+        assert_eq!((read, invalid), (2, 1));
+        assert_eq!(last_num, Some(11));
+    }
 }
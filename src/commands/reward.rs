@@ -0,0 +1,338 @@
+use crate::data::rewards::reward_audit_history;
+use crate::data::structs::{RewardKind, RoleReward};
+use crate::data::{BotData, load_guild_data, update_guild_data};
+use crate::rewards::{revoke_all, role_blocker};
+use crate::utils::{
+    check_admin, internal_err, localized_choice, log_info, reply_lang, slash_command, slash_option,
+    tr,
+};
+use serenity::all::*;
+
+// Audit entries shown by `/reward history`
+const HISTORY_LEN: i64 = 15;
+
+pub fn register() -> CreateCommand {
+    let kind = slash_option(CommandOptionType::String, "kind", "DESC_REWARD_ADD_KIND");
+    let kind = localized_choice(kind, "CHOICE_REWARD_LIFETIME", "lifetime_counts");
+    let kind = localized_choice(kind, "CHOICE_REWARD_MILESTONE", "milestone");
+    let kind = localized_choice(kind, "CHOICE_REWARD_MONTHLY_TOP", "monthly_top");
+
+    slash_command("reward", "DESC_REWARD")
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "add", "DESC_REWARD_ADD")
+                .add_sub_option(
+                    slash_option(CommandOptionType::Role, "role", "DESC_REWARD_ADD_ROLE")
+                        .required(true),
+                )
+                .add_sub_option(kind.required(true))
+                .add_sub_option(
+                    slash_option(
+                        CommandOptionType::Integer,
+                        "threshold",
+                        "DESC_REWARD_ADD_THRESHOLD",
+                    )
+                    .min_int_value(1)
+                    .required(false),
+                ),
+        )
+        .add_option(
+            slash_option(
+                CommandOptionType::SubCommand,
+                "remove",
+                "DESC_REWARD_REMOVE",
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::Role, "role", "DESC_REWARD_REMOVE_ROLE")
+                    .required(true),
+            ),
+        )
+        .add_option(slash_option(
+            CommandOptionType::SubCommand,
+            "list",
+            "DESC_REWARD_LIST",
+        ))
+        .add_option(slash_option(
+            CommandOptionType::SubCommand,
+            "history",
+            "DESC_REWARD_HISTORY",
+        ))
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: String) {
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
+        )
+        .await
+    {
+        internal_err(ctx, command, &e.to_string()).await;
+    }
+}
+
+fn option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a CommandDataOptionValue> {
+    options.iter().find(|o| o.name == name).map(|o| &o.value)
+}
+
+fn describe(reward: &RoleReward, lang: &str) -> String {
+    let key = match reward.kind {
+        RewardKind::LifetimeCounts => "REWARD_LIFETIME",
+        RewardKind::Milestone => "REWARD_MILESTONE",
+        RewardKind::MonthlyTop => "REWARD_MONTHLY_TOP",
+    };
+    tr(key, lang, &[("threshold", &reward.threshold)])
+}
+
+pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
+    if !check_admin(&ctx, &command, bot_data).await {
+        return;
+    }
+
+    let Some(guild_id) = command.guild_id else {
+        respond(
+            &ctx,
+            &command,
+            tr("NOT_IN_GUILD", &reply_lang(&command, None), &[]),
+        )
+        .await;
+        return;
+    };
+    let guild_id_u64 = guild_id.get();
+
+    let mut guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+        Ok(guild_data) => guild_data,
+        Err(e) => {
+            internal_err(&ctx, &command, &e.to_string()).await;
+            respond(
+                &ctx,
+                &command,
+                tr("DB_FETCH_FAILED", &reply_lang(&command, None), &[]),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let lang = reply_lang(&command, Some(&guild_data.settings.lang));
+
+    let Some(top) = command.data.options.first() else {
+        return;
+    };
+    let sub_options: &[CommandDataOption] = match &top.value {
+        CommandDataOptionValue::SubCommand(sub_options) => sub_options,
+        _ => &[],
+    };
+
+    match top.name.as_str() {
+        "add" => {
+            let Some(role_id) = option(sub_options, "role").and_then(|v| v.as_role_id()) else {
+                return;
+            };
+            let Some(kind) = option(sub_options, "kind")
+                .and_then(|v| v.as_str())
+                .and_then(RewardKind::from_name)
+            else {
+                return;
+            };
+            let threshold = option(sub_options, "threshold").and_then(|v| v.as_i64());
+            let threshold = match (kind, threshold) {
+                (RewardKind::MonthlyTop, _) => 0,
+                (_, Some(threshold)) => threshold,
+                (_, None) => {
+                    respond(
+                        &ctx,
+                        &command,
+                        tr("REWARD_NEED_THRESHOLD", &lang, &[("kind", &kind.name())]),
+                    )
+                    .await;
+                    return;
+                }
+            };
+
+            if guild_data
+                .settings
+                .role_rewards
+                .iter()
+                .any(|r| r.role_id == role_id.get())
+            {
+                respond(
+                    &ctx,
+                    &command,
+                    tr("REWARD_EXISTS", &lang, &[("role", &role_id)]),
+                )
+                .await;
+                return;
+            }
+            if let Some(blocker) = role_blocker(&ctx, guild_id, role_id, &lang).await {
+                respond(
+                    &ctx,
+                    &command,
+                    tr(
+                        "REWARD_BLOCKED",
+                        &lang,
+                        &[("role", &role_id), ("blocker", &blocker)],
+                    ),
+                )
+                .await;
+                return;
+            }
+
+            let reward = RoleReward {
+                role_id: role_id.get(),
+                kind,
+                threshold,
+            };
+            let reply = tr(
+                "REWARD_ADDED",
+                &lang,
+                &[("role", &role_id), ("condition", &describe(&reward, &lang))],
+            );
+            guild_data.settings.role_rewards.push(reward);
+
+            let role_rewards = guild_data.settings.role_rewards.clone();
+            if let Err(e) = update_guild_data(&bot_data.pool, guild_id_u64, |data| {
+                data.settings.role_rewards = role_rewards
+            })
+            .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+                return;
+            }
+            respond(&ctx, &command, reply).await;
+
+            log_info(&format!("🛠 Role reward added for Guild{guild_id_u64}"));
+        }
+
+        "remove" => {
+            let Some(role_id) = option(sub_options, "role").and_then(|v| v.as_role_id()) else {
+                return;
+            };
+            let Some(index) = guild_data
+                .settings
+                .role_rewards
+                .iter()
+                .position(|r| r.role_id == role_id.get())
+            else {
+                respond(
+                    &ctx,
+                    &command,
+                    tr("REWARD_UNKNOWN", &lang, &[("role", &role_id)]),
+                )
+                .await;
+                return;
+            };
+            guild_data.settings.role_rewards.remove(index);
+
+            let role_rewards = guild_data.settings.role_rewards.clone();
+            if let Err(e) = update_guild_data(&bot_data.pool, guild_id_u64, |data| {
+                data.settings.role_rewards = role_rewards
+            })
+            .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+                return;
+            }
+
+            // taking the role back can take a while on big servers
+            let _ = command.defer_ephemeral(&ctx.http).await;
+            let revoked = revoke_all(&ctx, &bot_data.pool, guild_id_u64, role_id.get()).await;
+
+            if let Err(e) = command
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(tr(
+                        "REWARD_REMOVED",
+                        &lang,
+                        &[("role", &role_id), ("revoked", &revoked)],
+                    )),
+                )
+                .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+            }
+
+            log_info(&format!("🛠 Role reward removed for Guild{guild_id_u64}"));
+        }
+
+        "list" => {
+            let reply = if guild_data.settings.role_rewards.is_empty() {
+                tr("REWARD_NONE", &lang, &[])
+            } else {
+                let mut lines = Vec::new();
+                for reward in &guild_data.settings.role_rewards {
+                    let role_id = RoleId::new(reward.role_id);
+                    let mut line = format!("<@&{role_id}> - {}", describe(reward, &lang));
+                    if let Some(blocker) = role_blocker(&ctx, guild_id, role_id, &lang).await {
+                        line.push_str(&format!("\n-# ⚠️ {blocker}"));
+                    }
+                    lines.push(line);
+                }
+                lines.join("\n")
+            };
+            respond(&ctx, &command, reply).await;
+        }
+
+        "history" => {
+            let entries =
+                match reward_audit_history(&bot_data.pool, guild_id_u64, HISTORY_LEN).await {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        internal_err(&ctx, &command, &e.to_string()).await;
+                        return;
+                    }
+                };
+
+            let reply = if entries.is_empty() {
+                tr("REWARD_HISTORY_EMPTY", &lang, &[])
+            } else {
+                entries
+                    .iter()
+                    .map(|e| {
+                        let emoji = match e.action.as_str() {
+                            "granted" => "➕",
+                            "revoked" => "➖",
+                            _ => "⚠️",
+                        };
+                        format!(
+                            "{emoji} <t:{}:R> <@{}> <@&{}> `{}`\n-# {}",
+                            e.created_at, e.user_id, e.role_id, e.action, e.reason
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            if let Err(e) = command
+                .create_response(
+                    &ctx.http,
+                    CreateInteractionResponse::Message(
+                        CreateInteractionResponseMessage::new()
+                            .content(reply)
+                            .allowed_mentions(CreateAllowedMentions::new())
+                            .flags(InteractionResponseFlags::EPHEMERAL),
+                    ),
+                )
+                .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+            }
+        }
+
+        _ => {
+            respond(
+                &ctx,
+                &command,
+                tr(
+                    "AVAILABLE_OPTIONS",
+                    &lang,
+                    &[("options", &"`add`, `remove`, `list`, `history`")],
+                ),
+            )
+            .await;
+        }
+    }
+}
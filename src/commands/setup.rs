@@ -1,136 +1,356 @@
-use serenity::all::{ChannelId, ChannelType};
+use serenity::all::{ChannelId, GuildId, MessageId, Permissions};
+use std::collections::BTreeSet;
 use serenity::prelude::*;
 use serenity::model::application::*;
 use serenity::builder::*;
 
-use crate::data::structs::GuildData;
-use crate::data::{BotData, load_guild_data, save_guild_data};
+use crate::audit::{format_change, record};
+use crate::commands::relog::{PROGRESS_PREFIX, relog_lock};
+use crate::components::confirm;
+use crate::data::audit::config_audit_history;
+use crate::data::snapshots::{list_snapshots, load_snapshot, save_snapshot};
+use crate::data::structs::{AccessControl, CountMode, CountingChannel, GuildData};
+use crate::data::{BotData, load_guild_data, lock_guild, save_guild_data, update_guild_data};
+use crate::i18n;
+use crate::threads::{countable_channel_types, join_if_thread};
+use crate::permissions::{describe_missing, missing_permissions, preflight};
 use crate::utils::*;
 
+// Separate counting games of one server, each logged on its own
+pub const MAX_COUNTING_CHANNELS: usize = 10;
+// Changes shown by `/setup history`
+const HISTORY_LEN: i64 = 15;
+const HISTORY_MAX_CHARS: usize = 1900;
+// Snapshots listed by `/setup restore`
+const SNAPSHOT_LIST_LEN: i64 = 15;
+
 pub fn register() -> CreateCommand {
-    CreateCommand::new("setup")
-        .description("Setup bundled commands")
+    slash_command("setup", "DESC_SETUP")
+        .add_option(slash_option(CommandOptionType::SubCommand, "info", "DESC_SETUP_INFO"))
+        .add_option(slash_option(CommandOptionType::SubCommand, "reset", "DESC_SETUP_RESET"))
         .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "info",
-                "Show info of current setup",
+            slash_option(CommandOptionType::SubCommand, "channels", "DESC_SETUP_CHANNELS")
+            .add_sub_option(
+                slash_option(CommandOptionType::Channel, "log_channel", "DESC_SETUP_LOG_CHANNEL")
+                .channel_types(countable_channel_types())
+                .required(true)
             )
-        )
-        .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "reset",
-                "Reset entire current setup",
+            .add_sub_option(
+                slash_option(CommandOptionType::Channel, "counting_channel", "DESC_SETUP_COUNTING_CHANNEL")
+                .channel_types(countable_channel_types())
+                .required(true)
             )
+            .add_sub_option(mode_option())
         )
         .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "channels",
-                "Set log and counting channels",
-            )
+            slash_option(CommandOptionType::SubCommand, "add_channel", "DESC_SETUP_ADD_CHANNEL")
             .add_sub_option(
-                CreateCommandOption::new(
-                    CommandOptionType::Channel,
-                    "log_channel",
-                    "Channel for logs",
-                )
-                .channel_types(vec![ChannelType::Text])
+                slash_option(CommandOptionType::Channel, "log_channel", "DESC_SETUP_ADD_CHANNEL_LOG")
+                .channel_types(countable_channel_types())
                 .required(true)
             )
             .add_sub_option(
-                CreateCommandOption::new(
-                    CommandOptionType::Channel,
-                    "counting_channel",
-                    "Channel for counting",
-                )
-                .channel_types(vec![ChannelType::Text])
+                slash_option(CommandOptionType::Channel, "counting_channel", "DESC_SETUP_COUNTING_CHANNEL")
+                .channel_types(countable_channel_types())
                 .required(true)
             )
+            .add_sub_option(mode_option())
         )
         .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "timezone",
-                "Set timezone(UTC) for logging clarity",
+            slash_option(CommandOptionType::SubCommand, "remove_channel", "DESC_SETUP_REMOVE_CHANNEL")
+            .add_sub_option(
+                slash_option(CommandOptionType::Channel, "counting_channel", "DESC_SETUP_REMOVE_CHANNEL_COUNTING")
+                .required(true)
             )
+        )
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "timezone", "DESC_SETUP_TIMEZONE")
             .add_sub_option(
-                CreateCommandOption::new(
-                    CommandOptionType::Integer,
-                    "utc",
-                    "Universal Time Coordinated",
-                )
+                slash_option(CommandOptionType::Integer, "utc", "DESC_SETUP_TIMEZONE_UTC")
                 .min_number_value(-12.0)
                 .max_number_value(14.0)
                 .required(true)
             )
         )
         .add_option(
-            CreateCommandOption::new(
-                CommandOptionType::SubCommand,
-                "language",
-                "Set language for your logs",
-            )
+            slash_option(CommandOptionType::SubCommand, "language", "DESC_SETUP_LANGUAGE")
+            .add_sub_option(lang_option("lang", "DESC_SETUP_LANGUAGE_LANG").required(true))
+            .add_sub_option(lang_option("lang2", "DESC_SETUP_LANGUAGE_LANG2").required(false))
+        )
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "public_api", "DESC_SETUP_PUBLIC_API")
             .add_sub_option(
-                CreateCommandOption::new(
-                    CommandOptionType::String,
-                    "lang",
-                    "Pick a language",
-                )
-                .add_string_choice("English", "en")
-                .add_string_choice("Japanese", "jp")
+                slash_option(CommandOptionType::Boolean, "enabled", "DESC_SETUP_PUBLIC_API_ENABLED")
                 .required(true)
             )
+        )
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "audit_channel", "DESC_SETUP_AUDIT_CHANNEL")
+            .add_sub_option(
+                slash_option(CommandOptionType::Channel, "channel", "DESC_SETUP_AUDIT_CHANNEL_CHANNEL")
+                .channel_types(countable_channel_types())
+                .required(false)
+            )
+        )
+        .add_option(slash_option(CommandOptionType::SubCommand, "history", "DESC_SETUP_HISTORY"))
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "restore", "DESC_SETUP_RESTORE")
             .add_sub_option(
-                CreateCommandOption::new(
-                    CommandOptionType::String,
-                    "lang2",
-                    "Pick a 2nd language",
-                )
-                .add_string_choice("English", "en")
-                .add_string_choice("Japanese", "jp")
+                slash_option(CommandOptionType::Integer, "snapshot", "DESC_SETUP_RESTORE_SNAPSHOT")
+                .min_int_value(1)
                 .required(false)
             )
         )
+        .add_option(
+            slash_option(CommandOptionType::SubCommandGroup, "permissions", "DESC_SETUP_PERMISSIONS")
+            .add_sub_option(slash_option(CommandOptionType::SubCommand, "list", "DESC_SETUP_PERMISSIONS_LIST"))
+            .add_sub_option(
+                slash_option(CommandOptionType::SubCommand, "add_manager", "DESC_SETUP_PERMISSIONS_ADD_MANAGER")
+                .add_sub_option(role_option())
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::SubCommand, "remove_manager", "DESC_SETUP_PERMISSIONS_REMOVE_MANAGER")
+                .add_sub_option(role_option())
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::SubCommand, "allow", "DESC_SETUP_PERMISSIONS_ALLOW")
+                .add_sub_option(command_option())
+                .add_sub_option(role_option())
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::SubCommand, "deny", "DESC_SETUP_PERMISSIONS_DENY")
+                .add_sub_option(command_option())
+                .add_sub_option(role_option())
+            )
+            .add_sub_option(
+                slash_option(CommandOptionType::SubCommand, "clear", "DESC_SETUP_PERMISSIONS_CLEAR")
+                .add_sub_option(command_option())
+            )
+        )
+}
+
+fn role_option() -> CreateCommandOption {
+    slash_option(CommandOptionType::Role, "role", "DESC_SETUP_PERMISSIONS_ROLE").required(true)
+}
+
+fn command_option() -> CreateCommandOption {
+    slash_option(CommandOptionType::String, "command", "DESC_SETUP_PERMISSIONS_COMMAND").required(true)
+}
+
+// Every language pack, by its native name (Discord takes up to 25 choices)
+// packs past Discord's choice limit are left out, `i18n::check` warns about them
+fn lang_option(name: &str, key: &str) -> CreateCommandOption {
+    let mut option = slash_option(CommandOptionType::String, name, key);
+    for (lang, pack) in i18n::languages().take(i18n::MAX_CHOICES) {
+        option = option.add_string_choice(&pack.name, lang);
+    }
+    option
+}
+
+// Commands gated by `check_admin`, `/setup permissions` itself stays with admins
+const ADMIN_COMMANDS: [&str; 5] = ["setup", "relog", "message", "reward", "webhook"];
+
+// "/Relog  formatonly" -> "relog formatonly", `None` for anything that isn't an admin command
+fn normalize_command(input: &str) -> Option<String> {
+    let path = input.trim().trim_start_matches('/').split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    let top = path.split(' ').next()?;
+    (ADMIN_COMMANDS.contains(&top) && !is_admin_only(&path)).then_some(path)
+}
+
+fn describe_channels(guild_data: &GuildData) -> String {
+    if guild_data.channels.is_empty() { return "none".to_string(); }
+    guild_data.channels.iter().map(describe_channel).collect::<Vec<_>>().join(", ")
+}
+
+fn describe_langs(lang: &str, lang2: Option<&str>) -> String {
+    format!("{lang} / {}", lang2.unwrap_or("(none)"))
+}
+
+fn describe_roles(roles: &[u64], lang: &str) -> String {
+    if roles.is_empty() { return tr("ADMINS_ONLY", lang, &[]); }
+    roles.iter().map(|id| format!("<@&{id}>")).collect::<Vec<_>>().join(", ")
+}
+
+fn mode_option() -> CreateCommandOption {
+    let mut option = slash_option(CommandOptionType::String, "mode", "DESC_SETUP_MODE").required(false);
+    for mode in CountMode::ALL {
+        option = option.add_string_choice(mode.name(), mode.name());
+    }
+    option
+}
+
+// Links shown in the confirmation before the rest is summed up
+const CONFIRM_LIST_LEN: usize = 15;
+
+// Messages this bot owns in a log channel: the tracked logs and helper of `channels`,
+// plus relog progress messages left behind among the latest ones
+async fn owned_log_messages<'a>(ctx: &Context, log_channel: ChannelId, channels: impl Iterator<Item = &'a CountingChannel>) -> Vec<MessageId> {
+    let mut owned: BTreeSet<MessageId> = BTreeSet::new();
+    for channel in channels.filter(|c| c.ids.log_channel_id == Some(log_channel.get())) {
+        owned.extend(channel.ids.log_msg_map.values().flat_map(|parts| parts.values()).map(|id| MessageId::new(*id)));
+        owned.extend(channel.ids.log_helper_msg_id.map(MessageId::new));
+    }
+
+    let bot_id = ctx.cache.current_user().id;
+    if let Ok(msgs) = log_channel.messages(&ctx.http, GetMessages::new().limit(100)).await {
+        owned.extend(msgs.iter().filter(|m| m.author.id == bot_id && m.content.starts_with(PROGRESS_PREFIX)).map(|m| m.id));
+    }
+
+    owned.into_iter().collect()
+}
+
+// Lists what's about to be deleted, `None` when there's nothing to delete
+fn describe_deletion(guild_id: GuildId, owned: &[(ChannelId, Vec<MessageId>)], lang: &str) -> Option<String> {
+    let total: usize = owned.iter().map(|(_, ids)| ids.len()).sum();
+    if total == 0 { return None; }
+
+    let mut lines: Vec<String> = owned
+        .iter()
+        .flat_map(|(channel_id, ids)| ids.iter().map(move |id| format!("https://discord.com/channels/{guild_id}/{channel_id}/{id}")))
+        .take(CONFIRM_LIST_LEN)
+        .collect();
+    if total > CONFIRM_LIST_LEN {
+        lines.push(tr("DELETION_MORE", lang, &[("more", &(total - CONFIRM_LIST_LEN))]));
+    }
+
+    Some(tr("DELETION_LIST", lang, &[("total", &total), ("links", &lines.join("\n"))]))
+}
+
+// Asks before deleting anything, `None` when there's nothing to delete (and nothing was answered yet)
+async fn confirm_deletion(ctx: &Context, command: &CommandInteraction, lang: &str, guild_id: GuildId, owned: &[(ChannelId, Vec<MessageId>)]) -> Option<bool> {
+    let deletion = describe_deletion(guild_id, owned, lang)?;
+    Some(confirm(ctx, command, lang, format!("❓ {deletion}"), tr("BUTTON_DELETE", lang, &[])).await)
+}
+
+// Keeps the setup as it is before a destructive change, tells the admin and returns false if that failed
+async fn snapshot(ctx: &Context, command: &CommandInteraction, lang: &str, bot_data: &BotData, guild_data: &GuildData, confirmed: bool) -> bool {
+    let guild_id_u64 = command.guild_id.map_or(0, |id| id.get());
+    match save_snapshot(&bot_data.pool, guild_id_u64, &command_path(command), guild_data).await {
+        Ok(()) => true,
+        Err(e) => {
+            log_error(&format!("Failed to snapshot Guild{guild_id_u64}: {e}"));
+            reply(ctx, command, tr("SNAPSHOT_FAILED", lang, &[]), confirmed).await;
+            false
+        }
+    }
+}
+
+// Whether `guild_data` has `channel` counted into the same log channel
+fn same_channel(guild_data: &GuildData, channel: &CountingChannel) -> bool {
+    guild_data.channels.iter().any(|c| c.ids.counting_channel_id == channel.ids.counting_channel_id && c.ids.log_channel_id == channel.ids.log_channel_id)
+}
+
+// Rolls the setup back to `restored`, but who may do what (permissions, webhooks, audit channel)
+// stays as it is now, and channels that stay keep their current logs while the others' are gone
+fn restore_setup(mut restored: GuildData, current: GuildData) -> GuildData {
+    for channel in restored.channels.iter_mut() {
+        let now = current.channels.iter().find(|c| c.ids.counting_channel_id == channel.ids.counting_channel_id && c.ids.log_channel_id == channel.ids.log_channel_id);
+        channel.ids.log_msg_map = now.map(|c| c.ids.log_msg_map.clone()).unwrap_or_default();
+        channel.ids.log_helper_msg_id = now.and_then(|c| c.ids.log_helper_msg_id);
+    }
+    restored.settings.access = current.settings.access;
+    restored.settings.webhooks = current.settings.webhooks;
+    restored.settings.audit_channel_id = current.settings.audit_channel_id;
+    restored
+}
+
+// Saves a settings change into freshly loaded data, counts may have been saved since the command started
+async fn update(ctx: &Context, command: &CommandInteraction, bot_data: &BotData, change: impl FnOnce(&mut GuildData)) -> Option<GuildData> {
+    let guild_id_u64 = command.guild_id.map_or(0, |id| id.get());
+    match update_guild_data(&bot_data.pool, guild_id_u64, change).await {
+        Ok(guild_data) => Some(guild_data),
+        Err(e) => {
+            internal_err(ctx, command, &e.to_string()).await;
+            None
+        }
+    }
+}
+
+// Answers the command, or replaces the confirmation once one was shown
+async fn reply(ctx: &Context, command: &CommandInteraction, content: String, confirmed: bool) {
+    let result = if confirmed {
+        command.edit_response(&ctx.http, EditInteractionResponse::new().content(content).components(vec![])).await.map(|_| ())
+    } else {
+        command.create_response(&ctx.http, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .flags(InteractionResponseFlags::EPHEMERAL)
+        )).await
+    };
+    if let Err(e) = result {
+        internal_err(ctx, command, &e.to_string()).await;
+    }
+}
+
+// Why the pair can't be used next to the other configured channels, `skip` is left out of the check
+fn channel_conflict(guild_data: &GuildData, log_channel: ChannelId, count_channel: ChannelId, skip: Option<usize>, lang: &str) -> Option<String> {
+    if log_channel == count_channel {
+        return Some(tr("CHANNELS_SAME", lang, &[]));
+    }
+
+    for (i, channel) in guild_data.channels.iter().enumerate() {
+        if Some(i) == skip { continue; }
+        if channel.ids.counting_channel_id == Some(count_channel.get()) {
+            return Some(tr("CHANNEL_ALREADY_COUNTING", lang, &[("channel", &count_channel)]));
+        }
+        if channel.ids.counting_channel_id == Some(log_channel.get()) {
+            return Some(tr("CHANNEL_COUNTING_NOT_LOG", lang, &[("channel", &log_channel)]));
+        }
+        if channel.ids.log_channel_id == Some(count_channel.get()) {
+            return Some(tr("CHANNEL_LOG_NOT_COUNTING", lang, &[("channel", &count_channel)]));
+        }
+    }
+
+    None
+}
+
+fn describe_channel(channel: &CountingChannel) -> String {
+    format!(
+        "<#{}> → <#{}> `{}`",
+        channel.ids.counting_channel_id.unwrap_or_default(),
+        channel.ids.log_channel_id.unwrap_or_default(),
+        channel.mode.name()
+    )
 }
 
 pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
-    if !check_admin(&ctx, &command).await { return; }
+    if !check_admin(&ctx, &command, bot_data).await { return; }
 
     if let Some(guild_id) = command.guild_id {
         let guild_id_u64 = guild_id.get();
         match load_guild_data(&bot_data.pool, guild_id_u64).await {
             Ok(mut guild_data) => {
+                let lang = reply_lang(&command, Some(&guild_data.settings.lang));
                 if let Some(top) = command.data.options.first() {
                     match top.name.as_str() {
                         "info" => {
                             if !guild_data.is_setup {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("❗ This server hasn't been setup yet!\nPlease use `/setup channels` to setup necessary channels.")
+                                        .content(tr("NOT_SETUP", &lang, &[]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
-                            } else if let (
-                                Some(log_ch_id),
-                                Some(count_ch_id)
-                            ) = (
-                                guild_data.ids.log_channel_id,
-                                guild_data.ids.counting_channel_id
-                            ) {
+                            } else if !guild_data.channels.is_empty() {
                                 let utc_format = get_utc_format(&guild_data.settings.utc);
-                                
-                                let reply = format!(
-                                    "`UTC {}`\n`lang`: {}\n`lang2`: {}\n`log_channel`: <#{}>\n`counting_channel`: <#{}>\n`auto_relog`: {}",
-                                    utc_format,
-                                    guild_data.settings.lang,
-                                    guild_data.settings.lang2.as_deref().unwrap_or("❓"),
-                                    log_ch_id,
-                                    count_ch_id,
-                                    if guild_data.settings.auto_relog {"✅"} else {"❌"}
-                                );
+
+                                let channels = guild_data.channels
+                                    .iter()
+                                    .enumerate()
+                                    .map(|(i, c)| if i == 0 { tr("INFO_PRIMARY", &lang, &[("channel", &describe_channel(c))]) } else { describe_channel(c) })
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+
+                                let reply = tr("INFO", &lang, &[
+                                    ("utc", &utc_format),
+                                    ("lang", &guild_data.settings.lang),
+                                    ("lang2", &guild_data.settings.lang2.as_deref().unwrap_or("❓")),
+                                    ("channels", &channels),
+                                    ("auto_relog", &if guild_data.settings.auto_relog {"✅"} else {"❌"}),
+                                    ("interval", &guild_data.settings.relog_interval),
+                                    ("public_api", &if guild_data.settings.public_api {"✅"} else {"❌"}),
+                                ]);
 
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
@@ -142,7 +362,7 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                             } else {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("❗ Missing Channel(s) in Configuration!\nPlease use `/setup channels` to setup channels again.")
+                                        .content(tr("MISSING_CHANNELS", &lang, &[]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
@@ -151,58 +371,183 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                         }
 
                         "reset" => {
-                            let is_default = guild_data.is_default_setup();
+                            if !guild_data.is_setup {
+                                reply(&ctx, &command, tr("NOT_SETUP", &lang, &[]), false).await;
+                                return;
+                            }
 
-                            if let Some(log_ch_id) = guild_data.ids.log_channel_id {
+                            let audit_channel = guild_data.settings.audit_channel_id;
+                            let old_setup = describe_channels(&guild_data);
+
+                            let log_channels: BTreeSet<u64> = guild_data.channels
+                                .iter()
+                                .filter_map(|c| c.ids.log_channel_id)
+                                .collect();
+                            let mut owned = Vec::new();
+                            for log_ch_id in log_channels {
                                 let log_channel = ChannelId::new(log_ch_id);
-                                while let Ok(msgs) = log_channel.messages(&ctx.http, GetMessages::new().limit(100)).await {
-                                    let bot_msgs: Vec<_> = msgs.into_iter().filter(|m| m.author.bot).collect();
-                                    if bot_msgs.is_empty() { break; }
-                                    if bot_msgs.len() > 1 {
-                                        let _ = log_channel.delete_messages(&ctx.http, &bot_msgs).await;
-                                    } else {
-                                        let _ = log_channel.delete_message(&ctx.http, bot_msgs[0].id).await;
+                                owned.push((log_channel, owned_log_messages(&ctx, log_channel, guild_data.channels.iter()).await));
+                            }
+
+                            // a reset always asks, even with no logs to delete
+                            let mut prompt = tr("RESET_CONFIRM", &lang, &[]);
+                            if let Some(deletion) = describe_deletion(guild_id, &owned, &lang) {
+                                prompt = format!("{prompt}\n{deletion}");
+                            }
+                            if !confirm(&ctx, &command, &lang, prompt, tr("BUTTON_RESET", &lang, &[])).await { return; }
+                            let confirmed = true;
+                            if !snapshot(&ctx, &command, &lang, bot_data, &guild_data, confirmed).await { return; }
+                            let mut deleted = 0;
+                            for (log_channel, ids) in &owned {
+                                deleted += delete_messages(&ctx.http, *log_channel, ids).await;
+                            }
+
+                            if !guild_data.is_default_setup() {
+                                let reset = update(&ctx, &command, bot_data, |data| {
+                                    *data = GuildData::default();
+                                    data.is_setup = false;
+                                }).await;
+                                if reset.is_none() { return; }
+                            }
+
+                            reply(&ctx, &command, tr("RESET_DONE", &lang, &[("deleted", &deleted)]), confirmed).await;
+                            record(&ctx, &bot_data.pool, &command, audit_channel, &old_setup, &format!("defaults, deleted {deleted} log messages")).await;
+
+                            log_info(format!("🛠 Reset Done for Guild{}", guild_id_u64).as_str());
+                        }
+
+                        "channels" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                            let log_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "log_channel")
+                                .and_then(|o| o.value.as_channel_id());
+
+                            let count_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "counting_channel")
+                                .and_then(|o| o.value.as_channel_id());
+
+                            let mode_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "mode")
+                                .and_then(|o| o.value.as_str())
+                                .and_then(CountMode::from_name);
+
+                            if let (Some(new_log_channel), Some(new_count_channel)) = (log_option, count_option) {
+                                if let Some(conflict) = channel_conflict(&guild_data, new_log_channel, new_count_channel, Some(0), &lang) {
+                                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                        CreateInteractionResponseMessage::new()
+                                            .content(conflict)
+                                            .flags(InteractionResponseFlags::EPHEMERAL)
+                                    )).await {
+                                        internal_err(&ctx, &command, &e.to_string()).await;
                                     }
+
+                                    return;
                                 }
-                            }
 
-                            if !guild_data.is_setup {
-                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                    CreateInteractionResponseMessage::new()
-                                        .content("❗ This server hasn't been setup yet!\nYou can use `/setup channels` to setup necessary channels.")
-                                        .flags(InteractionResponseFlags::EPHEMERAL)
-                                )).await {
-                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                join_if_thread(&ctx, new_log_channel).await;
+                                join_if_thread(&ctx, new_count_channel).await;
+
+                                let problems = preflight(&ctx, guild_id, new_log_channel, new_count_channel, &lang).await;
+                                if !problems.is_empty() {
+                                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                        CreateInteractionResponseMessage::new()
+                                            .content(tr("MISSING_PERMISSIONS", &lang, &[("problems", &problems.join("\n"))]))
+                                            .flags(InteractionResponseFlags::EPHEMERAL)
+                                    )).await {
+                                        internal_err(&ctx, &command, &e.to_string()).await;
+                                    }
+
+                                    return;
                                 }
-                            } else if !is_default {
-                                guild_data = GuildData::default();
-                                guild_data.is_setup = false;
 
-                                let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                                // log channels shared with the other counting channels keep their logs
+                                let shared: BTreeSet<u64> = guild_data.channels
+                                    .iter()
+                                    .skip(1)
+                                    .filter_map(|c| c.ids.log_channel_id)
+                                    .collect();
+                                let old_log_channel = guild_data.primary().and_then(|c| c.ids.log_channel_id);
+                                let purged_old = old_log_channel.is_some_and(|id| !shared.contains(&id));
+                                let mut owned = Vec::new();
+                                for log_ch_id in old_log_channel.into_iter().chain([new_log_channel.get()]).collect::<BTreeSet<_>>() {
+                                    if !shared.contains(&log_ch_id) {
+                                        let log_channel = ChannelId::new(log_ch_id);
+                                        owned.push((log_channel, owned_log_messages(&ctx, log_channel, guild_data.channels.iter().take(1)).await));
+                                    }
+                                }
 
-                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
-                                    CreateInteractionResponseMessage::new()
-                                        .content("✅ Reset Done!")
-                                        .flags(InteractionResponseFlags::EPHEMERAL)
-                                )).await {
-                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                let confirmed = match confirm_deletion(&ctx, &command, &lang, guild_id, &owned).await {
+                                    Some(false) => return,
+                                    Some(true) => true,
+                                    None => false,
+                                };
+                                // a relog would save its own copy over the new setup
+                                let lock = relog_lock(guild_id_u64).await;
+                                let Ok(_relog_guard) = lock.try_lock() else {
+                                    reply(&ctx, &command, tr("SETUP_RELOG_RUNNING", &lang, &[]), confirmed).await;
+                                    return;
+                                };
+                                if !snapshot(&ctx, &command, &lang, bot_data, &guild_data, confirmed).await { return; }
+                                for (log_channel, ids) in &owned {
+                                    delete_messages(&ctx.http, *log_channel, ids).await;
                                 }
 
-                                log_info(format!("🛠 Reset Done for Guild{}", guild_id_u64).as_str());
+                                // live counts may have been saved while the prompt was up
+                                let guard = lock_guild(guild_id_u64).await;
+                                guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                                    Ok(guild_data) => guild_data,
+                                    Err(e) => {
+                                        drop(guard);
+                                        internal_err(&ctx, &command, &e.to_string()).await;
+                                        return;
+                                    }
+                                };
+
+                                let old_primary = guild_data.primary().map_or("none".to_string(), describe_channel);
+
+                                // `/setup channels` always (re)configures the primary channel
+                                if guild_data.channels.is_empty() {
+                                    guild_data.channels.push(CountingChannel::default());
+                                }
+                                let primary = &mut guild_data.channels[0];
+                                if purged_old || primary.ids.log_channel_id != Some(new_log_channel.get()) {
+                                    primary.ids.log_msg_map.clear();
+                                    primary.ids.log_helper_msg_id = None;
+                                }
+                                primary.ids.log_channel_id = Some(new_log_channel.get());
+                                primary.ids.counting_channel_id = Some(new_count_channel.get());
+                                if let Some(mode) = mode_option {
+                                    primary.mode = mode;
+                                }
+                                let mode = primary.mode;
+                                guild_data.is_setup = true;
+
+                                let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                                drop(guard);
+
+                                let content = tr("SETUP_CHANNELS_DONE", &lang, &[
+                                    ("log_channel", &new_log_channel),
+                                    ("counting_channel", &new_count_channel),
+                                    ("mode", &mode.name()),
+                                ]);
+                                reply(&ctx, &command, content, confirmed).await;
+                                record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &old_primary, &describe_channel(&guild_data.channels[0])).await;
+
+                                log_info(format!("🛠 Setup 'Channel' Done for Guild{}", guild_id_u64).as_str());
                             } else {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("✅ Reset Done!")
+                                        .content(tr("CHANNELS_INCOMPLETE", &lang, &[]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
-
-                                log_info(format!("🛠 Reset Done for Guild{}", guild_id_u64).as_str());
                             }
-                        }
+                        }}
 
-                        "channels" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                        "add_channel" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
                             let log_option = sub_options
                                 .iter()
                                 .find(|o| o.name == "log_channel")
@@ -213,11 +558,26 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                 .find(|o| o.name == "counting_channel")
                                 .and_then(|o| o.value.as_channel_id());
 
+                            let mode = sub_options
+                                .iter()
+                                .find(|o| o.name == "mode")
+                                .and_then(|o| o.value.as_str())
+                                .and_then(CountMode::from_name)
+                                .unwrap_or_default();
+
                             if let (Some(new_log_channel), Some(new_count_channel)) = (log_option, count_option) {
-                                if new_log_channel.get() == new_count_channel.get() {
+                                let conflict = if guild_data.channels.is_empty() {
+                                    Some(tr("ADD_CHANNEL_NOT_SETUP", &lang, &[]))
+                                } else if guild_data.channels.len() >= MAX_COUNTING_CHANNELS {
+                                    Some(tr("ADD_CHANNEL_LIMIT", &lang, &[("max", &MAX_COUNTING_CHANNELS)]))
+                                } else {
+                                    channel_conflict(&guild_data, new_log_channel, new_count_channel, None, &lang)
+                                };
+
+                                if let Some(conflict) = conflict {
                                     if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                         CreateInteractionResponseMessage::new()
-                                            .content("❌ Can't set `log_channel` and `counting_channel` as the same.\nPlease try again")
+                                            .content(conflict)
                                             .flags(InteractionResponseFlags::EPHEMERAL)
                                     )).await {
                                         internal_err(&ctx, &command, &e.to_string()).await;
@@ -226,41 +586,33 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     return;
                                 }
 
-                                if let Some(log_ch_id) = guild_data.ids.log_channel_id {
-                                    let old_log_channel = ChannelId::new(log_ch_id);
+                                join_if_thread(&ctx, new_log_channel).await;
+                                join_if_thread(&ctx, new_count_channel).await;
 
-                                    while let Ok(msgs) = old_log_channel.messages(&ctx.http, GetMessages::new().limit(100)).await {
-                                        let bot_msgs: Vec<_> = msgs.into_iter().filter(|m| m.author.bot).collect();
-                                        if bot_msgs.is_empty() { break; }
-                                        if bot_msgs.len() > 1 {
-                                            let _ = old_log_channel.delete_messages(&ctx.http, &bot_msgs).await;
-                                        } else {
-                                            let _ = old_log_channel.delete_message(&ctx.http, bot_msgs[0].id).await;
-                                        }
+                                let problems = preflight(&ctx, guild_id, new_log_channel, new_count_channel, &lang).await;
+                                if !problems.is_empty() {
+                                    if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                        CreateInteractionResponseMessage::new()
+                                            .content(tr("MISSING_PERMISSIONS", &lang, &[("problems", &problems.join("\n"))]))
+                                            .flags(InteractionResponseFlags::EPHEMERAL)
+                                    )).await {
+                                        internal_err(&ctx, &command, &e.to_string()).await;
                                     }
-                                }
 
-                                while let Ok(msgs) = new_log_channel.messages(&ctx.http, GetMessages::new().limit(100)).await {
-                                    let bot_msgs: Vec<_> = msgs.into_iter().filter(|m| m.author.bot).collect();
-                                    if bot_msgs.is_empty() { break; }
-                                    if bot_msgs.len() > 1 {
-                                        let _ = new_log_channel.delete_messages(&ctx.http, &bot_msgs).await;
-                                    } else {
-                                        let _ = new_log_channel.delete_message(&ctx.http, bot_msgs[0].id).await;
-                                    }
+                                    return;
                                 }
 
-                                guild_data.ids.log_channel_id = Some(new_log_channel.get());
-                                guild_data.ids.counting_channel_id = Some(new_count_channel.get());
-                                guild_data.is_setup = true;
-
-                                let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                                let mut channel = CountingChannel { mode, ..Default::default() };
+                                channel.ids.log_channel_id = Some(new_log_channel.get());
+                                channel.ids.counting_channel_id = Some(new_count_channel.get());
+                                let Some(updated) = update(&ctx, &command, bot_data, |data| data.channels.push(channel)).await else { return; };
+                                guild_data = updated;
 
-                                let reply = format!(
-                                    "✅ Setup Done!\n`log_channel`: <#{}>\n`counting_channel`: <#{}>\n\nPlease ensure that this bot actually has necessary permissions for said channels set.",
-                                    new_log_channel.get(),
-                                    new_count_channel.get()
-                                );
+                                let reply = tr("ADD_CHANNEL_DONE", &lang, &[
+                                    ("log_channel", &new_log_channel),
+                                    ("counting_channel", &new_count_channel),
+                                    ("mode", &mode.name()),
+                                ]);
 
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
@@ -270,18 +622,88 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
 
-                                log_info(format!("🛠 Setup 'Channel' Done for Guild{}", guild_id_u64).as_str());
-                            } else {
+                                record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, "none", &describe_channel(guild_data.channels.last().unwrap())).await;
+
+                                log_info(format!("🛠 Setup 'Add Channel' Done for Guild{}", guild_id_u64).as_str());
+                            }
+                        }}
+
+                        "remove_channel" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                            let count_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "counting_channel")
+                                .and_then(|o| o.value.as_channel_id());
+
+                            let Some(index) = count_option.and_then(|id| {
+                                guild_data.channels.iter().position(|c| c.ids.counting_channel_id == Some(id.get()))
+                            }) else {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("❌ Can't set channels individually.\nPlease try again and set all necessary channels.")
+                                        .content(tr("REMOVE_CHANNEL_UNKNOWN", &lang, &[]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
+                                return;
+                            };
+
+                            // only its own logs go, a shared log channel keeps the others
+                            let mut owned = Vec::new();
+                            if let Some(log_ch_id) = guild_data.channels[index].ids.log_channel_id {
+                                let log_channel = ChannelId::new(log_ch_id);
+                                owned.push((log_channel, owned_log_messages(&ctx, log_channel, guild_data.channels[index..=index].iter()).await));
+                            }
 
+                            let confirmed = match confirm_deletion(&ctx, &command, &lang, guild_id, &owned).await {
+                                Some(false) => return,
+                                Some(true) => true,
+                                None => false,
+                            };
+                            // a relog would save the removed channel back
+                            let lock = relog_lock(guild_id_u64).await;
+                            let Ok(_relog_guard) = lock.try_lock() else {
+                                reply(&ctx, &command, tr("SETUP_RELOG_RUNNING", &lang, &[]), confirmed).await;
                                 return;
+                            };
+                            if !snapshot(&ctx, &command, &lang, bot_data, &guild_data, confirmed).await { return; }
+                            for (log_channel, ids) in &owned {
+                                delete_messages(&ctx.http, *log_channel, ids).await;
                             }
+
+                            // live counts may have been saved while the prompt was up
+                            let guard = lock_guild(guild_id_u64).await;
+                            guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                                Ok(guild_data) => guild_data,
+                                Err(e) => {
+                                    drop(guard);
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                    return;
+                                }
+                            };
+                            let Some(index) = count_option.and_then(|id| {
+                                guild_data.channels.iter().position(|c| c.ids.counting_channel_id == Some(id.get()))
+                            }) else {
+                                drop(guard);
+                                reply(&ctx, &command, tr("REMOVE_CHANNEL_UNKNOWN", &lang, &[]), confirmed).await;
+                                return;
+                            };
+
+                            let removed = guild_data.channels.remove(index);
+                            guild_data.is_setup = !guild_data.channels.is_empty();
+
+                            let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                            drop(guard);
+
+                            let mut content = tr("REMOVE_CHANNEL_DONE", &lang, &[("channel", &describe_channel(&removed))]);
+                            if guild_data.channels.is_empty() {
+                                content.push_str(&tr("REMOVE_CHANNEL_LAST", &lang, &[]));
+                            } else if index == 0 {
+                                content.push_str(&tr("REMOVE_CHANNEL_NEW_PRIMARY", &lang, &[("channel", &describe_channel(&guild_data.channels[0]))]));
+                            }
+                            reply(&ctx, &command, content, confirmed).await;
+                            record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &describe_channel(&removed), "removed").await;
+
+                            log_info(format!("🛠 Setup 'Remove Channel' Done for Guild{}", guild_id_u64).as_str());
                         }}
 
                         "timezone" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
@@ -291,16 +713,13 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                 .and_then(|o| o.value.as_i64());
 
                             if let Some(new_timezone) = timezone_option {
-                                guild_data.settings.utc = new_timezone.clamp(-12, 14) as i8;
-
-                                let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                                let old_utc = get_utc_format(&guild_data.settings.utc);
+                                let Some(updated) = update(&ctx, &command, bot_data, |data| data.settings.utc = new_timezone.clamp(-12, 14) as i8).await else { return; };
+                                guild_data = updated;
 
                                 let utc_format = get_utc_format(&guild_data.settings.utc);
 
-                                let reply = format!(
-                                    "✅ Setup Done!\n`UTC {}`",
-                                    utc_format.as_str()
-                                );
+                                let reply = tr("SETUP_TIMEZONE_DONE", &lang, &[("utc", &utc_format)]);
 
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
@@ -310,17 +729,17 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
 
+                                record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &format!("UTC {old_utc}"), &format!("UTC {utc_format}")).await;
+
                                 log_info(format!("🛠 Setup 'Timezone' Done for Guild{}", guild_id_u64).as_str());
                             } else {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("❌ Missing `utc` variable.\nPlease try again")
+                                        .content(tr("MISSING_VARIABLE", &lang, &[("name", &"utc")]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
-
-                                return;
                             }
                         }}
 
@@ -336,14 +755,10 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                 .and_then(|o| o.value.as_str());
 
                             if let Some(new_lang) = lang_option {
-                                let langs = vec!["en", "jp"];
-
-                                let valid_lang2 = lang2_option.map_or(true, |l| langs.contains(&l));
-
-                                if !langs.contains(&new_lang) || !valid_lang2 {
+                                if !i18n::is_available(new_lang) || !lang2_option.is_none_or(i18n::is_available) {
                                     if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                         CreateInteractionResponseMessage::new()
-                                            .content("❌ Can't set languages other than available selections.\nPlease try again")
+                                            .content(tr("LANGUAGE_UNKNOWN", &lang, &[]))
                                             .flags(InteractionResponseFlags::EPHEMERAL)
                                     )).await {
                                         internal_err(&ctx, &command, &e.to_string()).await;
@@ -352,16 +767,52 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                     return;
                                 }
 
-                                guild_data.settings.lang = new_lang.to_string();
-                                guild_data.settings.lang2 = lang2_option.map(str::to_string);
+                                let old_langs = describe_langs(&guild_data.settings.lang, guild_data.settings.lang2.as_deref());
+                                let Some(updated) = update(&ctx, &command, bot_data, |data| {
+                                    data.settings.lang = new_lang.to_string();
+                                    data.settings.lang2 = lang2_option.map(str::to_string);
+                                }).await else { return; };
+                                guild_data = updated;
 
-                                let _ = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                                let reply = tr("SETUP_LANGUAGE_DONE", &lang, &[
+                                    ("lang", &new_lang),
+                                    ("lang2", &lang2_option.unwrap_or("(none)")),
+                                ]);
 
-                                let reply = format!(
-                                    "✅ Setup Done!\n`lang`: {}\n`lang2`: {}",
-                                    new_lang,
-                                    lang2_option.unwrap_or("(none)")
-                                );
+                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                    CreateInteractionResponseMessage::new()
+                                        .content(reply)
+                                        .flags(InteractionResponseFlags::EPHEMERAL)
+                                )).await {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                }
+                                record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &old_langs, &describe_langs(new_lang, lang2_option)).await;
+                            } else {
+                                if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                    CreateInteractionResponseMessage::new()
+                                        .content(tr("MISSING_VARIABLE", &lang, &[("name", &"lang")]))
+                                        .flags(InteractionResponseFlags::EPHEMERAL)
+                                )).await {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                }
+                            }
+                        }}
+
+                        "public_api" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                            let enabled_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "enabled")
+                                .and_then(|o| o.value.as_bool());
+
+                            if let Some(enabled) = enabled_option {
+                                let was_enabled = guild_data.settings.public_api;
+                                let Some(updated) = update(&ctx, &command, bot_data, |data| data.settings.public_api = enabled).await else { return; };
+                                guild_data = updated;
+
+                                let mut reply = tr("SETUP_PUBLIC_API_DONE", &lang, &[("enabled", &if enabled {"✅"} else {"❌"})]);
+                                if enabled {
+                                    reply.push_str(&tr("PUBLIC_API_URL", &lang, &[("guild", &guild_id_u64)]));
+                                }
 
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
@@ -370,23 +821,266 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
+
+                                record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &was_enabled.to_string(), &enabled.to_string()).await;
+
+                                log_info(format!("🛠 Setup 'Public API' Done for Guild{}", guild_id_u64).as_str());
                             } else {
                                 if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                     CreateInteractionResponseMessage::new()
-                                        .content("❌ Missing `lang` variable.\nPlease try again")
+                                        .content(tr("MISSING_VARIABLE", &lang, &[("name", &"enabled")]))
                                         .flags(InteractionResponseFlags::EPHEMERAL)
                                 )).await {
                                     internal_err(&ctx, &command, &e.to_string()).await;
                                 }
+                            }
+                        }}
+
+                        "audit_channel" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                            let channel_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "channel")
+                                .and_then(|o| o.value.as_channel_id());
+
+                            if let Some(channel_id) = channel_option {
+                                join_if_thread(&ctx, channel_id).await;
+                                let required = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+                                let problem = match missing_permissions(&ctx, guild_id, channel_id, required, &lang).await {
+                                    Ok(missing) if missing.is_empty() => None,
+                                    Ok(missing) => Some(describe_missing(channel_id, missing, &lang)),
+                                    Err(problem) => Some(problem),
+                                };
+                                if let Some(problem) = problem {
+                                    reply(&ctx, &command, tr("MISSING_PERMISSIONS", &lang, &[("problems", &problem)]), false).await;
+                                    return;
+                                }
+                            }
+
+                            let describe = |id: Option<u64>| id.map_or("none".to_string(), |id| format!("<#{id}>"));
+                            let old_channel = guild_data.settings.audit_channel_id;
+                            let Some(updated) = update(&ctx, &command, bot_data, |data| data.settings.audit_channel_id = channel_option.map(|id| id.get())).await else { return; };
+                            guild_data = updated;
+
+                            let audit_channel = guild_data.settings.audit_channel_id.map_or(tr("NONE", &lang, &[]), |id| format!("<#{id}>"));
+                            reply(&ctx, &command, tr("SETUP_AUDIT_CHANNEL_DONE", &lang, &[("channel", &audit_channel)]), false).await;
+                            // turning it off still shows up in the old channel
+                            let audit_channel = guild_data.settings.audit_channel_id.or(old_channel);
+                            record(&ctx, &bot_data.pool, &command, audit_channel, &describe(old_channel), &describe(guild_data.settings.audit_channel_id)).await;
+
+                            log_info(format!("🛠 Setup 'Audit Channel' Done for Guild{}", guild_id_u64).as_str());
+                        }}
+
+                        "history" => {
+                            let changes = match config_audit_history(&bot_data.pool, guild_id_u64, HISTORY_LEN).await {
+                                Ok(changes) => changes,
+                                Err(e) => {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                    return;
+                                }
+                            };
+
+                            let content = if changes.is_empty() {
+                                tr("HISTORY_EMPTY", &lang, &[])
+                            } else {
+                                // newest first, as many as fit in one message
+                                let mut content = String::new();
+                                for line in changes.iter().map(format_change) {
+                                    if content.len() + line.len() + 1 > HISTORY_MAX_CHARS { break; }
+                                    content.push_str(&line);
+                                    content.push('\n');
+                                }
+                                content
+                            };
+
+                            if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
+                                CreateInteractionResponseMessage::new()
+                                    .content(content)
+                                    .allowed_mentions(CreateAllowedMentions::new())
+                                    .flags(InteractionResponseFlags::EPHEMERAL)
+                            )).await {
+                                internal_err(&ctx, &command, &e.to_string()).await;
+                            }
+                        }
+
+                        "restore" => { if let CommandDataOptionValue::SubCommand(sub_options) = &top.value {
+                            let snapshot_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "snapshot")
+                                .and_then(|o| o.value.as_i64());
+
+                            let Some(snapshot_id) = snapshot_option else {
+                                let content = match list_snapshots(&bot_data.pool, guild_id_u64, SNAPSHOT_LIST_LEN).await {
+                                    Ok(snapshots) if snapshots.is_empty() => tr("SNAPSHOTS_EMPTY", &lang, &[]),
+                                    Ok(snapshots) => tr("SNAPSHOTS_LIST", &lang, &[(
+                                        "snapshots",
+                                        &snapshots.iter().map(|s| tr("SNAPSHOT_ENTRY", &lang, &[("id", &s.id), ("created_at", &s.created_at), ("reason", &s.reason)])).collect::<Vec<_>>().join("\n"),
+                                    )]),
+                                    Err(e) => {
+                                        internal_err(&ctx, &command, &e.to_string()).await;
+                                        return;
+                                    }
+                                };
+                                reply(&ctx, &command, content, false).await;
+                                return;
+                            };
+
+                            let restored = match load_snapshot(&bot_data.pool, guild_id_u64, snapshot_id).await {
+                                Ok(Some(restored)) => restored,
+                                Ok(None) => {
+                                    reply(&ctx, &command, tr("SNAPSHOT_UNKNOWN", &lang, &[("id", &snapshot_id)]), false).await;
+                                    return;
+                                }
+                                Err(e) => {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                    return;
+                                }
+                            };
 
+                            // logs of channels that don't come back (or move) would be left behind
+                            let orphaned: Vec<&CountingChannel> = guild_data.channels.iter().filter(|c| !same_channel(&restored, c)).collect();
+                            let log_channels: BTreeSet<u64> = orphaned.iter().filter_map(|c| c.ids.log_channel_id).collect();
+                            let mut owned = Vec::new();
+                            for log_ch_id in log_channels {
+                                let log_channel = ChannelId::new(log_ch_id);
+                                owned.push((log_channel, owned_log_messages(&ctx, log_channel, orphaned.iter().copied()).await));
+                            }
+
+                            let mut prompt = tr("RESTORE_CONFIRM", &lang, &[("id", &snapshot_id), ("channels", &describe_channels(&restored))]);
+                            if let Some(deletion) = describe_deletion(guild_id, &owned, &lang) {
+                                prompt = format!("{prompt}\n{deletion}");
+                            }
+                            if !confirm(&ctx, &command, &lang, prompt, tr("BUTTON_RESTORE", &lang, &[])).await { return; }
+                            let confirmed = true;
+
+                            // a relog would save its own copy over the restored setup
+                            let lock = relog_lock(guild_id_u64).await;
+                            let Ok(_guard) = lock.try_lock() else {
+                                reply(&ctx, &command, tr("RESTORE_RELOG_RUNNING", &lang, &[]), confirmed).await;
                                 return;
+                            };
+                            // anything may have changed while the prompt was up
+                            guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                                Ok(guild_data) => guild_data,
+                                Err(e) => {
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                    return;
+                                }
+                            };
+
+                            // rolling back is a destructive change too
+                            if !snapshot(&ctx, &command, &lang, bot_data, &guild_data, confirmed).await { return; }
+                            for (log_channel, ids) in &owned {
+                                delete_messages(&ctx.http, *log_channel, ids).await;
                             }
+
+                            // log messages may have been posted while deleting the old ones
+                            let guard = lock_guild(guild_id_u64).await;
+                            guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                                Ok(guild_data) => guild_data,
+                                Err(e) => {
+                                    drop(guard);
+                                    internal_err(&ctx, &command, &e.to_string()).await;
+                                    return;
+                                }
+                            };
+                            let old_setup = describe_channels(&guild_data);
+                            let audit_channel = guild_data.settings.audit_channel_id;
+                            guild_data = restore_setup(restored, guild_data);
+
+                            let saved = save_guild_data(&bot_data.pool, guild_id_u64, &guild_data).await;
+                            drop(guard);
+                            if let Err(e) = saved {
+                                internal_err(&ctx, &command, &e.to_string()).await;
+                                return;
+                            }
+                            crate::scheduler::reschedule(guild_id_u64).await;
+
+                            reply(&ctx, &command, tr("SNAPSHOT_RESTORED", &lang, &[("id", &snapshot_id), ("channels", &describe_channels(&guild_data))]), confirmed).await;
+                            record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id.or(audit_channel), &old_setup, &format!("snapshot #{snapshot_id}: {}", describe_channels(&guild_data))).await;
+
+                            log_info(format!("🛠 Setup 'Restore' Done for Guild{}", guild_id_u64).as_str());
                         }}
 
+                        "permissions" => {
+                            let CommandDataOptionValue::SubCommandGroup(group_options) = &top.value else { return; };
+                            let Some(sub) = group_options.first() else { return; };
+                            let sub_options: &[CommandDataOption] = match &sub.value {
+                                CommandDataOptionValue::SubCommand(sub_options) => sub_options,
+                                _ => &[],
+                            };
+                            let role_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "role")
+                                .and_then(|o| o.value.as_role_id())
+                                .map(|id| id.get());
+                            let command_option = sub_options
+                                .iter()
+                                .find(|o| o.name == "command")
+                                .and_then(|o| o.value.as_str());
+                            let path = command_option.and_then(normalize_command);
+                            if command_option.is_some() && path.is_none() {
+                                reply(&ctx, &command, tr("NOT_ADMIN_COMMAND", &lang, &[("command", &command_option.unwrap_or_default()), ("commands", &ADMIN_COMMANDS.map(|c| format!("`{c}`")).join(", "))]), false).await;
+                                return;
+                            }
+
+                            let access = &mut guild_data.settings.access;
+                            let describe_access = |access: &AccessControl| match &path {
+                                Some(path) => format!("/{path}: {}", describe_roles(access.allowed_roles(path), "en")),
+                                None => format!("managers: {}", describe_roles(&access.manager_roles, "en")),
+                            };
+                            let old_access = describe_access(access);
+                            let content = match (sub.name.as_str(), role_option, path.clone()) {
+                                ("list", _, _) => {
+                                    let mut lines = vec![tr("PERMISSIONS_MANAGERS", &lang, &[("roles", &describe_roles(&access.manager_roles, &lang))])];
+                                    lines.extend(access.overrides.iter().map(|(path, roles)| format!("`/{path}`: {}", describe_roles(roles, &lang))));
+                                    reply(&ctx, &command, lines.join("\n"), false).await;
+                                    return;
+                                }
+                                ("add_manager", Some(role_id), _) => {
+                                    if !access.manager_roles.contains(&role_id) { access.manager_roles.push(role_id); }
+                                    tr("PERMISSIONS_MANAGER_ADDED", &lang, &[("role", &role_id)])
+                                }
+                                ("remove_manager", Some(role_id), _) => {
+                                    access.manager_roles.retain(|id| *id != role_id);
+                                    tr("PERMISSIONS_MANAGER_REMOVED", &lang, &[("role", &role_id)])
+                                }
+                                // a new override starts from who can use the command right now
+                                ("allow", Some(role_id), Some(path)) => {
+                                    let current = access.allowed_roles(&path).to_vec();
+                                    let roles = access.overrides.entry(path.clone()).or_insert(current);
+                                    if !roles.contains(&role_id) { roles.push(role_id); }
+                                    format!("✅ `/{path}`: {}", describe_roles(roles, &lang))
+                                }
+                                ("deny", Some(role_id), Some(path)) => {
+                                    let current = access.allowed_roles(&path).to_vec();
+                                    let roles = access.overrides.entry(path.clone()).or_insert(current);
+                                    roles.retain(|id| *id != role_id);
+                                    format!("✅ `/{path}`: {}", describe_roles(roles, &lang))
+                                }
+                                ("clear", _, Some(path)) => {
+                                    if access.overrides.remove(&path).is_none() {
+                                        reply(&ctx, &command, tr("PERMISSIONS_NO_OVERRIDE", &lang, &[("path", &path)]), false).await;
+                                        return;
+                                    }
+                                    tr("PERMISSIONS_CLEARED", &lang, &[("path", &path), ("roles", &describe_roles(access.allowed_roles(&path), &lang))])
+                                }
+                                _ => return,
+                            };
+                            let new_access = describe_access(access);
+
+                            let access = access.clone();
+                            let Some(updated) = update(&ctx, &command, bot_data, |data| data.settings.access = access).await else { return; };
+                            guild_data = updated;
+                            reply(&ctx, &command, content, false).await;
+                            record(&ctx, &bot_data.pool, &command, guild_data.settings.audit_channel_id, &old_access, &new_access).await;
+
+                            log_info(format!("🛠 Setup 'Permissions' Done for Guild{}", guild_id_u64).as_str());
+                        }
+
                         _ => {
                             if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                                 CreateInteractionResponseMessage::new()
-                                    .content("❓ Available options: `info`, `reset`, `channels`, `timezone`, `language`")
+                                    .content(tr("AVAILABLE_OPTIONS", &lang, &[("options", &"`info`, `reset`, `channels`, `add_channel`, `remove_channel`, `timezone`, `language`, `public_api`, `audit_channel`, `history`, `restore`, `permissions`")]))
                                     .flags(InteractionResponseFlags::EPHEMERAL)
                             )).await {
                                 internal_err(&ctx, &command, &e.to_string()).await;
@@ -399,7 +1093,7 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
                 internal_err(&ctx, &command, &e.to_string()).await;
                 if let Err(e2) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
-                        .content("❗ Failed to fetch from Database\nPlease report the problem to developer...")
+                        .content(tr("DB_FETCH_FAILED", &reply_lang(&command, None), &[]))
                         .flags(InteractionResponseFlags::EPHEMERAL)
                 )).await {
                     internal_err(&ctx, &command, &e2.to_string()).await;
@@ -409,10 +1103,26 @@ pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotDa
     } else {
         if let Err(e) = command.create_response(&ctx.http, CreateInteractionResponse::Message(
             CreateInteractionResponseMessage::new()
-                .content("❗ This command can only be used within a discord server!")
+                .content(tr("NOT_IN_GUILD", &reply_lang(&command, None), &[]))
                 .flags(InteractionResponseFlags::EPHEMERAL)
         )).await {
             internal_err(&ctx, &command, &e.to_string()).await;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_command_cleans_admin_commands() {
+        assert_eq!(normalize_command(" /Relog  formatonly ").as_deref(), Some("relog formatonly"));
+        assert_eq!(normalize_command("webhook").as_deref(), Some("webhook"));
+        assert_eq!(normalize_command("ping"), None);
+        assert_eq!(normalize_command(""), None);
+        // permissions and restore can't be handed out
+        assert_eq!(normalize_command("setup permissions"), None);
+        assert_eq!(normalize_command("/setup restore"), None);
+    }
+}
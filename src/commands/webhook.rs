@@ -0,0 +1,391 @@
+use crate::audit::record;
+use crate::data::structs::{GuildData, Webhook, WebhookEvent};
+use crate::data::{BotData, load_guild_data, update_guild_data};
+use crate::utils::{
+    check_admin, internal_err, log_info, log_warn, reply_lang, slash_command, slash_option, tr,
+};
+use crate::webhooks::{
+    DEFAULT_MILESTONE_EVERY, DeliveryError, MAX_WEBHOOKS, deliver, new_secret, redact_url,
+    resolve_public,
+};
+use serde_json::json;
+use serenity::all::*;
+use std::collections::BTreeSet;
+
+pub fn register() -> CreateCommand {
+    slash_command("webhook", "DESC_WEBHOOK")
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "add", "DESC_WEBHOOK_ADD")
+                .add_sub_option(
+                    slash_option(CommandOptionType::String, "url", "DESC_WEBHOOK_ADD_URL")
+                        .required(true),
+                )
+                .add_sub_option(
+                    slash_option(
+                        CommandOptionType::String,
+                        "events",
+                        "DESC_WEBHOOK_ADD_EVENTS",
+                    )
+                    .required(false),
+                )
+                .add_sub_option(
+                    slash_option(
+                        CommandOptionType::Integer,
+                        "milestone_every",
+                        "DESC_WEBHOOK_ADD_MILESTONE",
+                    )
+                    .min_int_value(1)
+                    .required(false),
+                ),
+        )
+        .add_option(
+            slash_option(
+                CommandOptionType::SubCommand,
+                "remove",
+                "DESC_WEBHOOK_REMOVE",
+            )
+            .add_sub_option(index_option()),
+        )
+        .add_option(slash_option(
+            CommandOptionType::SubCommand,
+            "list",
+            "DESC_WEBHOOK_LIST",
+        ))
+        .add_option(
+            slash_option(CommandOptionType::SubCommand, "test", "DESC_WEBHOOK_TEST")
+                .add_sub_option(index_option()),
+        )
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: String) {
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
+        )
+        .await
+    {
+        internal_err(ctx, command, &e.to_string()).await;
+    }
+}
+
+fn event_names() -> String {
+    WebhookEvent::ALL
+        .iter()
+        .map(|e| format!("`{}`", e.name()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a CommandDataOptionValue> {
+    options.iter().find(|o| o.name == name).map(|o| &o.value)
+}
+
+fn index_option() -> CreateCommandOption {
+    slash_option(CommandOptionType::Integer, "index", "DESC_WEBHOOK_INDEX")
+        .min_int_value(1)
+        .required(true)
+}
+
+fn find_webhook<'a>(
+    guild_data: &'a GuildData,
+    index: Option<i64>,
+    lang: &str,
+) -> Result<(usize, &'a Webhook), String> {
+    index
+        .and_then(|i| usize::try_from(i - 1).ok())
+        .and_then(|i| guild_data.settings.webhooks.get(i).map(|w| (i, w)))
+        .ok_or_else(|| tr("WEBHOOK_UNKNOWN", lang, &[]))
+}
+
+pub async fn execute(ctx: Context, command: CommandInteraction, bot_data: &BotData) {
+    if !check_admin(&ctx, &command, bot_data).await {
+        return;
+    }
+
+    let Some(guild_id) = command.guild_id else {
+        respond(
+            &ctx,
+            &command,
+            tr("NOT_IN_GUILD", &reply_lang(&command, None), &[]),
+        )
+        .await;
+        return;
+    };
+    let guild_id_u64 = guild_id.get();
+
+    let mut guild_data = match load_guild_data(&bot_data.pool, guild_id_u64).await {
+        Ok(guild_data) => guild_data,
+        Err(e) => {
+            internal_err(&ctx, &command, &e.to_string()).await;
+            respond(
+                &ctx,
+                &command,
+                tr("DB_FETCH_FAILED", &reply_lang(&command, None), &[]),
+            )
+            .await;
+            return;
+        }
+    };
+
+    let lang = reply_lang(&command, Some(&guild_data.settings.lang));
+
+    let Some(top) = command.data.options.first() else {
+        return;
+    };
+    let sub_options: &[CommandDataOption] = match &top.value {
+        CommandDataOptionValue::SubCommand(sub_options) => sub_options,
+        _ => &[],
+    };
+
+    match top.name.as_str() {
+        "add" => {
+            let url = option(sub_options, "url")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                respond(&ctx, &command, tr("WEBHOOK_INVALID_URL", &lang, &[])).await;
+                return;
+            }
+            if resolve_public(&url).await.is_err() {
+                respond(&ctx, &command, tr("WEBHOOK_BLOCKED_URL", &lang, &[])).await;
+                return;
+            }
+            if guild_data.settings.webhooks.len() >= MAX_WEBHOOKS {
+                respond(
+                    &ctx,
+                    &command,
+                    tr("WEBHOOK_LIMIT", &lang, &[("max", &MAX_WEBHOOKS)]),
+                )
+                .await;
+                return;
+            }
+
+            let mut events = BTreeSet::new();
+            if let Some(names) = option(sub_options, "events").and_then(|v| v.as_str()) {
+                for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    match WebhookEvent::from_name(name) {
+                        Some(event) => {
+                            events.insert(event);
+                        }
+                        None => {
+                            respond(
+                                &ctx,
+                                &command,
+                                tr(
+                                    "WEBHOOK_UNKNOWN_EVENT",
+                                    &lang,
+                                    &[("name", &name), ("events", &event_names())],
+                                ),
+                            )
+                            .await;
+                            return;
+                        }
+                    }
+                }
+            }
+
+            let webhook = Webhook {
+                url,
+                secret: new_secret(),
+                events,
+                milestone_every: option(sub_options, "milestone_every")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(DEFAULT_MILESTONE_EVERY),
+            };
+            let reply = tr(
+                "WEBHOOK_ADDED",
+                &lang,
+                &[
+                    ("index", &(guild_data.settings.webhooks.len() + 1)),
+                    ("url", &webhook.url),
+                    ("events", &describe_events(&webhook)),
+                    ("milestone_every", &webhook.milestone_every),
+                    ("secret", &webhook.secret),
+                ],
+            );
+            let added = describe_webhook(guild_data.settings.webhooks.len(), &webhook);
+            guild_data.settings.webhooks.push(webhook);
+
+            let webhooks = guild_data.settings.webhooks.clone();
+            if let Err(e) = update_guild_data(&bot_data.pool, guild_id_u64, |data| {
+                data.settings.webhooks = webhooks
+            })
+            .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+                return;
+            }
+            respond(&ctx, &command, reply).await;
+            record(
+                &ctx,
+                &bot_data.pool,
+                &command,
+                guild_data.settings.audit_channel_id,
+                "none",
+                &added,
+            )
+            .await;
+
+            log_info(&format!("🛠 Webhook added for Guild{guild_id_u64}"));
+        }
+
+        "remove" => {
+            let index = match find_webhook(
+                &guild_data,
+                option(sub_options, "index").and_then(|v| v.as_i64()),
+                &lang,
+            ) {
+                Ok((index, _)) => index,
+                Err(reply) => {
+                    respond(&ctx, &command, reply).await;
+                    return;
+                }
+            };
+            let removed = guild_data.settings.webhooks.remove(index);
+
+            let webhooks = guild_data.settings.webhooks.clone();
+            if let Err(e) = update_guild_data(&bot_data.pool, guild_id_u64, |data| {
+                data.settings.webhooks = webhooks
+            })
+            .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+                return;
+            }
+            respond(
+                &ctx,
+                &command,
+                tr(
+                    "WEBHOOK_REMOVED",
+                    &lang,
+                    &[("index", &(index + 1)), ("url", &removed.url)],
+                ),
+            )
+            .await;
+            record(
+                &ctx,
+                &bot_data.pool,
+                &command,
+                guild_data.settings.audit_channel_id,
+                &describe_webhook(index, &removed),
+                "removed",
+            )
+            .await;
+
+            log_info(&format!("🛠 Webhook removed for Guild{guild_id_u64}"));
+        }
+
+        "list" => {
+            let reply = if guild_data.settings.webhooks.is_empty() {
+                tr("WEBHOOK_NONE", &lang, &[])
+            } else {
+                guild_data
+                    .settings
+                    .webhooks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, w)| {
+                        tr(
+                            "WEBHOOK_LIST_ENTRY",
+                            &lang,
+                            &[
+                                ("index", &(i + 1)),
+                                ("url", &w.url),
+                                ("events", &describe_events(w)),
+                                ("milestone_every", &w.milestone_every),
+                            ],
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            respond(&ctx, &command, reply).await;
+        }
+
+        "test" => {
+            let webhook = match find_webhook(
+                &guild_data,
+                option(sub_options, "index").and_then(|v| v.as_i64()),
+                &lang,
+            ) {
+                Ok((_, webhook)) => webhook.clone(),
+                Err(reply) => {
+                    respond(&ctx, &command, reply).await;
+                    return;
+                }
+            };
+
+            let _ = command.defer_ephemeral(&ctx.http).await;
+
+            let reply = match deliver(
+                &webhook,
+                guild_id_u64,
+                "ping",
+                json!({ "message": "Hello from CountLogger 💙" }),
+            )
+            .await
+            {
+                Ok(status) if status.is_success() => {
+                    tr("WEBHOOK_TEST_OK", &lang, &[("status", &status)])
+                }
+                Ok(status) => tr("WEBHOOK_TEST_FAILED", &lang, &[("status", &status)]),
+                Err(DeliveryError::Blocked) => tr("WEBHOOK_BLOCKED_URL", &lang, &[]),
+                // the error text could describe the bot's own network, it only goes to the log
+                Err(e) => {
+                    log_warn(&format!("Webhook test for Guild{guild_id_u64} failed: {e}"));
+                    tr("WEBHOOK_UNREACHABLE", &lang, &[])
+                }
+            };
+
+            if let Err(e) = command
+                .edit_response(&ctx.http, EditInteractionResponse::new().content(reply))
+                .await
+            {
+                internal_err(&ctx, &command, &e.to_string()).await;
+            }
+        }
+
+        _ => {
+            respond(
+                &ctx,
+                &command,
+                tr(
+                    "AVAILABLE_OPTIONS",
+                    &lang,
+                    &[("options", &"`add`, `remove`, `list`, `test`")],
+                ),
+            )
+            .await;
+        }
+    }
+}
+
+// Audit log value, the secret and the URL's token stay out of it
+fn describe_webhook(index: usize, webhook: &Webhook) -> String {
+    format!(
+        "#{} {} ({})",
+        index + 1,
+        redact_url(&webhook.url),
+        describe_events(webhook)
+    )
+}
+
+fn describe_events(webhook: &Webhook) -> String {
+    if webhook.events.is_empty() {
+        "`all`".to_string()
+    } else {
+        webhook
+            .events
+            .iter()
+            .map(|e| format!("`{}`", e.name()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
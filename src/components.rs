@@ -0,0 +1,191 @@
+use crate::utils::{locale_lang, log_warn, tr};
+use once_cell::sync::Lazy;
+use serenity::all::{
+    ButtonStyle, CommandInteraction, ComponentInteraction, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
+    InteractionId, InteractionResponseFlags, UserId,
+};
+use serenity::prelude::Context;
+use std::collections::HashMap;
+use tokio::sync::{Mutex, oneshot};
+use tokio::time::{Duration, timeout};
+
+/// How long a confirmation waits for its buttons
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+struct Waiting {
+    user_id: UserId,
+    sender: oneshot::Sender<ComponentInteraction>,
+}
+
+// Prompts waiting for a press, keyed by the interaction that asked
+static WAITING: Lazy<Mutex<HashMap<InteractionId, Waiting>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A button of a prompt, its custom id is scoped to the asking command
+pub struct Button {
+    pub action: &'static str,
+    pub label: String,
+    pub style: ButtonStyle,
+}
+
+/// Answers `command` with `content` and buttons, then waits for whoever ran it to press one.
+/// The press comes back unacknowledged with its action, `None` once it timed out
+/// (the prompt says so by then, in `lang`)
+pub async fn prompt(
+    ctx: &Context,
+    command: &CommandInteraction,
+    lang: &str,
+    content: String,
+    buttons: &[Button],
+    wait: Duration,
+) -> Option<(&'static str, ComponentInteraction)> {
+    let (sender, receiver) = oneshot::channel();
+    WAITING.lock().await.insert(
+        command.id,
+        Waiting {
+            user_id: command.user.id,
+            sender,
+        },
+    );
+
+    let row = CreateActionRow::Buttons(
+        buttons
+            .iter()
+            .map(|b| {
+                CreateButton::new(format!("{}:{}", command.id, b.action))
+                    .label(&b.label)
+                    .style(b.style)
+            })
+            .collect(),
+    );
+    if let Err(e) = command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .components(vec![row])
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
+        )
+        .await
+    {
+        WAITING.lock().await.remove(&command.id);
+        log_warn(&format!("Failed to send prompt: {e}"));
+        return None;
+    }
+
+    let Ok(Ok(press)) = timeout(wait, receiver).await else {
+        WAITING.lock().await.remove(&command.id);
+        let _ = command
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(tr("PROMPT_TIMED_OUT", lang, &[]))
+                    .components(vec![]),
+            )
+            .await;
+        return None;
+    };
+
+    let action = press.data.custom_id.split_once(':').map(|(_, a)| a)?;
+    let action = buttons.iter().find(|b| b.action == action)?.action;
+    Some((action, press))
+}
+
+/// Asks to confirm a destructive command. True once confirmed, the command is answered
+/// with `edit_response` from then on; false when it was cancelled or timed out (already told)
+pub async fn confirm(
+    ctx: &Context,
+    command: &CommandInteraction,
+    lang: &str,
+    content: String,
+    confirm_label: String,
+) -> bool {
+    let buttons = [
+        Button {
+            action: "confirm",
+            label: confirm_label,
+            style: ButtonStyle::Danger,
+        },
+        Button {
+            action: "cancel",
+            label: tr("BUTTON_CANCEL", lang, &[]),
+            style: ButtonStyle::Secondary,
+        },
+    ];
+    let Some((action, press)) =
+        prompt(ctx, command, lang, content, &buttons, CONFIRM_TIMEOUT).await
+    else {
+        return false;
+    };
+
+    if action == "confirm" {
+        let _ = press
+            .create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+            .await;
+        true
+    } else {
+        let _ = press
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .content(tr("PROMPT_CANCELLED", lang, &[]))
+                        .components(vec![]),
+                ),
+            )
+            .await;
+        false
+    }
+}
+
+/// Hands a button press to the prompt waiting for it (called from `interaction_create`)
+pub async fn dispatch(ctx: Context, component: ComponentInteraction) {
+    let asking = component
+        .data
+        .custom_id
+        .split_once(':')
+        .and_then(|(id, _)| id.parse::<u64>().ok())
+        .map(InteractionId::new);
+
+    let waiting = match asking {
+        Some(id) => {
+            let mut waiting = WAITING.lock().await;
+            match waiting.get(&id) {
+                // only whoever ran the command gets to answer
+                Some(w) if w.user_id != component.user.id => {
+                    drop(waiting);
+                    respond(&ctx, &component, "PROMPT_NOT_YOURS").await;
+                    return;
+                }
+                Some(_) => waiting.remove(&id),
+                None => None,
+            }
+        }
+        None => None,
+    };
+
+    match waiting {
+        Some(waiting) => {
+            let _ = waiting.sender.send(component);
+        }
+        // the prompt timed out or the bot restarted in between
+        None => respond(&ctx, &component, "PROMPT_EXPIRED").await,
+    }
+}
+
+// Answers a press nobody waits for, in the presser's language
+async fn respond(ctx: &Context, component: &ComponentInteraction, key: &str) {
+    let _ = component
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(tr(key, &locale_lang(&component.locale), &[]))
+                    .flags(InteractionResponseFlags::EPHEMERAL),
+            ),
+        )
+        .await;
+}
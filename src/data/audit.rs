@@ -0,0 +1,71 @@
+use crate::metrics::db_timer;
+use sqlx::PgPool;
+
+pub struct ConfigChange {
+    pub user_id: u64,
+    pub command: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub created_at: i64, // unix seconds
+}
+
+pub async fn add_config_audit(
+    pool: &PgPool,
+    guild_id: u64,
+    user_id: u64,
+    command: &str,
+    old_value: &str,
+    new_value: &str,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("add_config_audit");
+    sqlx::query!(
+        r#"
+        insert into public.config_audit (guild_id, user_id, command, old_value, new_value)
+        values ($1, $2, $3, $4, $5)
+        "#,
+        guild_id as i64,
+        user_id as i64,
+        command,
+        old_value,
+        new_value
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Latest configuration changes of the guild, newest first
+pub async fn config_audit_history(
+    pool: &PgPool,
+    guild_id: u64,
+    limit: i64,
+) -> Result<Vec<ConfigChange>, sqlx::Error> {
+    let _timer = db_timer("config_audit_history");
+    let rows = sqlx::query!(
+        r#"
+        select
+            user_id, command, old_value, new_value,
+            extract(epoch from created_at)::bigint as "created_at!"
+        from public.config_audit
+        where guild_id = $1
+        order by id desc
+        limit $2
+        "#,
+        guild_id as i64,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ConfigChange {
+            user_id: r.user_id as u64,
+            command: r.command,
+            old_value: r.old_value,
+            new_value: r.new_value,
+            created_at: r.created_at,
+        })
+        .collect())
+}
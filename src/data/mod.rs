@@ -1,11 +1,54 @@
+pub mod audit;
+pub mod rewards;
+pub mod snapshots;
+pub mod stats;
 pub mod structs;
 
 use chrono::{DateTime, Utc};
-use serde_json;
+use once_cell::sync::Lazy;
 use serenity::prelude::TypeMapKey;
 use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use structs::*;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock, RwLockWriteGuard};
+
+// Every save holds a read guard, shutdown takes the write guard to wait them out
+static SAVE_GATE: Lazy<RwLock<()>> = Lazy::new(|| RwLock::new(()));
+static PENDING_SAVES: AtomicUsize = AtomicUsize::new(0);
+
+/// Waits for in-flight saves to finish and blocks new ones while the guard lives,
+/// returns how many saves were still pending
+pub async fn flush_saves() -> (RwLockWriteGuard<'static, ()>, usize) {
+    let pending = PENDING_SAVES.load(Ordering::SeqCst);
+    (SAVE_GATE.write().await, pending)
+}
+
+static GUILD_LOCKS: Lazy<Mutex<HashMap<u64, Arc<Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Serializes load → change → save of one guild's data between writers that run concurrently
+/// (live counts, background relogs). Only hold it around the load and the save, never across
+/// Discord calls
+pub async fn lock_guild(guild_id: u64) -> OwnedMutexGuard<()> {
+    let lock = Arc::clone(GUILD_LOCKS.lock().await.entry(guild_id).or_default());
+    lock.lock_owned().await
+}
+
+/// Loads the guild's data, applies `change` and saves it under `lock_guild`. For writers that
+/// only change settings, so they don't save over counts stored since they loaded the guild
+pub async fn update_guild_data(
+    pool: &sqlx::PgPool,
+    guild_id: u64,
+    change: impl FnOnce(&mut GuildData),
+) -> Result<GuildData, sqlx::Error> {
+    let _guard = lock_guild(guild_id).await;
+    let mut data = load_guild_data(pool, guild_id).await?;
+    change(&mut data);
+    save_guild_data(pool, guild_id, &data).await?;
+    Ok(data)
+}
 
 pub struct BotData {
     pub pool: Arc<Pool<Postgres>>,
@@ -38,20 +81,23 @@ impl TypeMapKey for BotDataKey {
 //         .unwrap_or_default()
 // }
 pub async fn load_all_data(pool: &sqlx::PgPool) -> Result<AllGuildData, sqlx::Error> {
+    let timer = crate::metrics::db_timer("load_all_data");
     let rows = sqlx::query!(
         r#"
         select
             guild_id,
             is_setup,
-            utc, lang, lang2, auto_relog,
+            utc, lang, lang2, auto_relog, relog_interval, public_api, webhooks, role_rewards, access,
+            audit_channel_id,
             log_channel_id, counting_channel_id, log_msg_map,
             last_scanned_msg_id, log_helper_msg_id,
-            daily_counts
+            daily_counts, channels
         from public.guilds
         "#
     )
     .fetch_all(pool)
     .await?;
+    timer.observe_duration();
 
     let mut map = std::collections::HashMap::new();
 
@@ -65,15 +111,28 @@ pub async fn load_all_data(pool: &sqlx::PgPool) -> Result<AllGuildData, sqlx::Er
                     lang: r.lang,
                     lang2: r.lang2,
                     auto_relog: r.auto_relog,
+                    relog_interval: r.relog_interval,
+                    public_api: r.public_api,
+                    webhooks: serde_json::from_value(r.webhooks).unwrap_or_default(),
+                    role_rewards: serde_json::from_value(r.role_rewards).unwrap_or_default(),
+                    access: serde_json::from_value(r.access).unwrap_or_default(),
+                    audit_channel_id: r.audit_channel_id.map(|v| v as u64),
                 },
-                ids: IDs {
-                    log_channel_id: r.log_channel_id.map(|v| v as u64),
-                    counting_channel_id: r.counting_channel_id.map(|v| v as u64),
-                    log_msg_map: serde_json::from_value(r.log_msg_map).unwrap(),
-                    last_scanned_msg_id: r.last_scanned_msg_id.map(|v| v as u64),
-                    log_helper_msg_id: r.log_helper_msg_id.map(|v| v as u64),
-                },
-                daily_counts: serde_json::from_value(r.daily_counts).unwrap(),
+                channels: load_channels(
+                    r.channels,
+                    CountingChannel {
+                        mode: CountMode::Normal,
+                        ids: IDs {
+                            log_channel_id: r.log_channel_id.map(|v| v as u64),
+                            counting_channel_id: r.counting_channel_id.map(|v| v as u64),
+                            log_msg_map: serde_json::from_value(r.log_msg_map).unwrap(),
+                            last_scanned_msg_id: r.last_scanned_msg_id.map(|v| v as u64),
+                            log_helper_msg_id: r.log_helper_msg_id.map(|v| v as u64),
+                        },
+                        daily_counts: serde_json::from_value(r.daily_counts).unwrap(),
+                        last_count: None,
+                    },
+                ),
             },
         );
     }
@@ -81,6 +140,41 @@ pub async fn load_all_data(pool: &sqlx::PgPool) -> Result<AllGuildData, sqlx::Er
     Ok(AllGuildData(map))
 }
 
+/// The columns the scheduler needs to know which guilds are due
+pub struct ScheduledGuild {
+    pub guild_id: u64,
+    pub is_setup: bool,
+    pub auto_relog: bool,
+    pub relog_interval: i32,
+    pub utc: i8,
+    pub role_rewards: Vec<RoleReward>,
+}
+
+pub async fn load_schedules(pool: &sqlx::PgPool) -> Result<Vec<ScheduledGuild>, sqlx::Error> {
+    let timer = crate::metrics::db_timer("load_schedules");
+    let rows = sqlx::query!(
+        r#"
+        select guild_id, is_setup, auto_relog, relog_interval, utc, role_rewards
+        from public.guilds
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    timer.observe_duration();
+
+    Ok(rows
+        .into_iter()
+        .map(|r| ScheduledGuild {
+            guild_id: r.guild_id as u64,
+            is_setup: r.is_setup,
+            auto_relog: r.auto_relog,
+            relog_interval: r.relog_interval,
+            utc: r.utc as i8,
+            role_rewards: serde_json::from_value(r.role_rewards).unwrap_or_default(),
+        })
+        .collect())
+}
+
 // pub fn save_guild_data(guild_id: u64, data: &GuildData) {
 //     let mut all_data = load_all_data();
 //     all_data.0.insert(guild_id, data.clone());
@@ -91,21 +185,31 @@ pub async fn save_guild_data(
     guild_id: u64,
     data: &GuildData,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+    PENDING_SAVES.fetch_add(1, Ordering::SeqCst);
+    let _gate = SAVE_GATE.read().await;
+    let timer = crate::metrics::db_timer("save_guild_data");
+
+    // legacy columns follow the primary channel
+    let no_channel = CountingChannel::default();
+    let primary = data.primary().unwrap_or(&no_channel);
+
+    let result = sqlx::query!(
         r#"
         insert into public.guilds (
             guild_id, is_setup,
-            utc, lang, lang2, auto_relog,
+            utc, lang, lang2, auto_relog, relog_interval, public_api, webhooks, role_rewards, access,
+            audit_channel_id,
             log_channel_id, counting_channel_id, log_msg_map,
             last_scanned_msg_id, log_helper_msg_id,
-            daily_counts
+            daily_counts, channels
         )
         values (
             $1, $2,
-            $3, $4, $5, $6,
-            $7, $8, $9,
-            $10, $11,
-            $12
+            $3, $4, $5, $6, $7, $8, $9, $10, $11,
+            $12,
+            $13, $14, $15,
+            $16, $17,
+            $18, $19
         )
         on conflict (guild_id)
         do update set
@@ -114,12 +218,19 @@ pub async fn save_guild_data(
             lang = excluded.lang,
             lang2 = excluded.lang2,
             auto_relog = excluded.auto_relog,
+            relog_interval = excluded.relog_interval,
+            public_api = excluded.public_api,
+            webhooks = excluded.webhooks,
+            role_rewards = excluded.role_rewards,
+            access = excluded.access,
+            audit_channel_id = excluded.audit_channel_id,
             log_channel_id = excluded.log_channel_id,
             counting_channel_id = excluded.counting_channel_id,
             log_msg_map = excluded.log_msg_map,
             last_scanned_msg_id = excluded.last_scanned_msg_id,
             log_helper_msg_id = excluded.log_helper_msg_id,
             daily_counts = excluded.daily_counts,
+            channels = excluded.channels,
             updated_at = now()
         "#,
         guild_id as i64,
@@ -128,15 +239,26 @@ pub async fn save_guild_data(
         data.settings.lang,
         data.settings.lang2,
         data.settings.auto_relog,
-        data.ids.log_channel_id.map(|v| v as i64),
-        data.ids.counting_channel_id.map(|v| v as i64),
-        serde_json::to_value(&data.ids.log_msg_map).unwrap(),
-        data.ids.last_scanned_msg_id.map(|v| v as i64),
-        data.ids.log_helper_msg_id.map(|v| v as i64),
-        serde_json::to_value(&data.daily_counts).unwrap(),
+        data.settings.relog_interval,
+        data.settings.public_api,
+        serde_json::to_value(&data.settings.webhooks).unwrap(),
+        serde_json::to_value(&data.settings.role_rewards).unwrap(),
+        serde_json::to_value(&data.settings.access).unwrap(),
+        data.settings.audit_channel_id.map(|v| v as i64),
+        primary.ids.log_channel_id.map(|v| v as i64),
+        primary.ids.counting_channel_id.map(|v| v as i64),
+        serde_json::to_value(&primary.ids.log_msg_map).unwrap(),
+        primary.ids.last_scanned_msg_id.map(|v| v as i64),
+        primary.ids.log_helper_msg_id.map(|v| v as i64),
+        serde_json::to_value(&primary.daily_counts).unwrap(),
+        serde_json::to_value(&data.channels).unwrap(),
     )
     .execute(pool)
-    .await?;
+    .await;
+    timer.observe_duration();
+
+    PENDING_SAVES.fetch_sub(1, Ordering::SeqCst);
+    result?;
 
     Ok(())
 }
@@ -176,18 +298,20 @@ pub async fn load_guild_data(pool: &sqlx::PgPool, guild_id: u64) -> Result<Guild
 
 //     default_data
 // }
-async fn try_load_guild_data(
+pub async fn try_load_guild_data(
     pool: &sqlx::PgPool,
     guild_id: u64,
 ) -> Result<Option<GuildData>, sqlx::Error> {
+    let timer = crate::metrics::db_timer("load_guild_data");
     let row = sqlx::query!(
         r#"
         select
             is_setup,
-            utc, lang, lang2, auto_relog,
+            utc, lang, lang2, auto_relog, relog_interval, public_api, webhooks, role_rewards, access,
+            audit_channel_id,
             log_channel_id, counting_channel_id, log_msg_map,
             last_scanned_msg_id, log_helper_msg_id,
-            daily_counts
+            daily_counts, channels
         from guilds
         where guild_id = $1
         "#,
@@ -195,6 +319,7 @@ async fn try_load_guild_data(
     )
     .fetch_optional(pool)
     .await?;
+    timer.observe_duration();
 
     let Some(r) = row else {
         return Ok(None);
@@ -207,14 +332,43 @@ async fn try_load_guild_data(
             lang: r.lang,
             lang2: r.lang2,
             auto_relog: r.auto_relog,
+            relog_interval: r.relog_interval,
+            public_api: r.public_api,
+            webhooks: serde_json::from_value(r.webhooks).unwrap_or_default(),
+            role_rewards: serde_json::from_value(r.role_rewards).unwrap_or_default(),
+            access: serde_json::from_value(r.access).unwrap_or_default(),
+            audit_channel_id: r.audit_channel_id.map(|v| v as u64),
         },
-        ids: IDs {
-            log_channel_id: r.log_channel_id.map(|v| v as u64),
-            counting_channel_id: r.counting_channel_id.map(|v| v as u64),
-            log_msg_map: serde_json::from_value(r.log_msg_map).unwrap(),
-            last_scanned_msg_id: r.last_scanned_msg_id.map(|v| v as u64),
-            log_helper_msg_id: r.log_helper_msg_id.map(|v| v as u64),
-        },
-        daily_counts: serde_json::from_value(r.daily_counts).unwrap(),
+        channels: load_channels(
+            r.channels,
+            CountingChannel {
+                mode: CountMode::Normal,
+                ids: IDs {
+                    log_channel_id: r.log_channel_id.map(|v| v as u64),
+                    counting_channel_id: r.counting_channel_id.map(|v| v as u64),
+                    log_msg_map: serde_json::from_value(r.log_msg_map).unwrap(),
+                    last_scanned_msg_id: r.last_scanned_msg_id.map(|v| v as u64),
+                    log_helper_msg_id: r.log_helper_msg_id.map(|v| v as u64),
+                },
+                daily_counts: serde_json::from_value(r.daily_counts).unwrap(),
+                last_count: None,
+            },
+        ),
     }))
 }
+
+// Guilds saved before `channels` existed only have the legacy single channel columns
+fn load_channels(channels: serde_json::Value, legacy: CountingChannel) -> Vec<CountingChannel> {
+    let channels: Vec<CountingChannel> = serde_json::from_value(channels).unwrap_or_default();
+    if !channels.is_empty() {
+        return channels;
+    }
+
+    if legacy.ids.counting_channel_id.is_none()
+        && legacy.ids.log_channel_id.is_none()
+        && legacy.daily_counts.is_empty()
+    {
+        return Vec::new();
+    }
+    vec![legacy]
+}
@@ -0,0 +1,210 @@
+use crate::metrics::db_timer;
+use sqlx::PgPool;
+
+pub struct AuditEntry {
+    pub user_id: u64,
+    pub role_id: u64,
+    pub action: String,
+    pub reason: String,
+    pub created_at: i64, // unix seconds
+}
+
+/// Adds one valid count to the user's tally of `month` (`YYYY-MM`)
+pub async fn record_user_count(
+    pool: &PgPool,
+    guild_id: u64,
+    user_id: u64,
+    month: &str,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("record_user_count");
+    sqlx::query!(
+        r#"
+        insert into public.user_month_counts (guild_id, user_id, month, counts)
+        values ($1, $2, $3, 1)
+        on conflict (guild_id, user_id, month)
+        do update set counts = user_month_counts.counts + 1
+        "#,
+        guild_id as i64,
+        user_id as i64,
+        month
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn lifetime_counts(
+    pool: &PgPool,
+    guild_id: u64,
+    user_id: u64,
+) -> Result<i64, sqlx::Error> {
+    let _timer = db_timer("lifetime_counts");
+    let row = sqlx::query!(
+        r#"
+        select coalesce(sum(counts), 0)::bigint as "lifetime!"
+        from public.user_month_counts
+        where guild_id = $1 and user_id = $2
+        "#,
+        guild_id as i64,
+        user_id as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.lifetime)
+}
+
+/// Users of `month` with their counts, most counts first
+pub async fn month_ranking(
+    pool: &PgPool,
+    guild_id: u64,
+    month: &str,
+) -> Result<Vec<(u64, i64)>, sqlx::Error> {
+    let _timer = db_timer("month_ranking");
+    let rows = sqlx::query!(
+        r#"
+        select user_id, counts
+        from public.user_month_counts
+        where guild_id = $1 and month = $2
+        order by counts desc
+        limit 25
+        "#,
+        guild_id as i64,
+        month
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.user_id as u64, r.counts))
+        .collect())
+}
+
+pub async fn reward_holders(
+    pool: &PgPool,
+    guild_id: u64,
+    role_id: u64,
+) -> Result<Vec<u64>, sqlx::Error> {
+    let _timer = db_timer("reward_holders");
+    let rows = sqlx::query!(
+        r#"
+        select user_id
+        from public.role_reward_holders
+        where guild_id = $1 and role_id = $2
+        "#,
+        guild_id as i64,
+        role_id as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.user_id as u64).collect())
+}
+
+pub async fn add_reward_holder(
+    pool: &PgPool,
+    guild_id: u64,
+    role_id: u64,
+    user_id: u64,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("add_reward_holder");
+    sqlx::query!(
+        r#"
+        insert into public.role_reward_holders (guild_id, role_id, user_id)
+        values ($1, $2, $3)
+        on conflict do nothing
+        "#,
+        guild_id as i64,
+        role_id as i64,
+        user_id as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_reward_holder(
+    pool: &PgPool,
+    guild_id: u64,
+    role_id: u64,
+    user_id: u64,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("remove_reward_holder");
+    sqlx::query!(
+        r#"
+        delete from public.role_reward_holders
+        where guild_id = $1 and role_id = $2 and user_id = $3
+        "#,
+        guild_id as i64,
+        role_id as i64,
+        user_id as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn add_reward_audit(
+    pool: &PgPool,
+    guild_id: u64,
+    user_id: u64,
+    role_id: u64,
+    action: &str,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("add_reward_audit");
+    sqlx::query!(
+        r#"
+        insert into public.role_reward_audit (guild_id, user_id, role_id, action, reason)
+        values ($1, $2, $3, $4, $5)
+        "#,
+        guild_id as i64,
+        user_id as i64,
+        role_id as i64,
+        action,
+        reason
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Latest reward audit entries of the guild, newest first
+pub async fn reward_audit_history(
+    pool: &PgPool,
+    guild_id: u64,
+    limit: i64,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let _timer = db_timer("reward_audit_history");
+    let rows = sqlx::query!(
+        r#"
+        select
+            user_id, role_id, action, reason,
+            extract(epoch from created_at)::bigint as "created_at!"
+        from public.role_reward_audit
+        where guild_id = $1
+        order by id desc
+        limit $2
+        "#,
+        guild_id as i64,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| AuditEntry {
+            user_id: r.user_id as u64,
+            role_id: r.role_id as u64,
+            action: r.action,
+            reason: r.reason,
+            created_at: r.created_at,
+        })
+        .collect())
+}
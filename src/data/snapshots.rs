@@ -0,0 +1,111 @@
+use crate::data::structs::GuildData;
+use crate::metrics::db_timer;
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+use std::env;
+
+// Days a snapshot is kept before it's pruned
+static RETENTION_DAYS: Lazy<i32> = Lazy::new(|| {
+    env::var("SNAPSHOT_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30)
+        .max(1)
+});
+
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub reason: String,
+    pub created_at: i64, // unix seconds
+}
+
+/// Stores the guild's data as it is right now, pruning its expired snapshots on the way
+pub async fn save_snapshot(
+    pool: &PgPool,
+    guild_id: u64,
+    reason: &str,
+    data: &GuildData,
+) -> Result<(), sqlx::Error> {
+    let _timer = db_timer("save_snapshot");
+    sqlx::query!(
+        r#"
+        insert into public.guild_snapshots (guild_id, reason, data)
+        values ($1, $2, $3)
+        "#,
+        guild_id as i64,
+        reason,
+        serde_json::to_value(data).unwrap()
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        delete from public.guild_snapshots
+        where guild_id = $1 and created_at < now() - make_interval(days => $2)
+        "#,
+        guild_id as i64,
+        *RETENTION_DAYS
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Snapshots still retained for the guild, newest first
+pub async fn list_snapshots(
+    pool: &PgPool,
+    guild_id: u64,
+    limit: i64,
+) -> Result<Vec<SnapshotInfo>, sqlx::Error> {
+    let _timer = db_timer("list_snapshots");
+    let rows = sqlx::query!(
+        r#"
+        select
+            id, reason,
+            extract(epoch from created_at)::bigint as "created_at!"
+        from public.guild_snapshots
+        where guild_id = $1 and created_at >= now() - make_interval(days => $3)
+        order by id desc
+        limit $2
+        "#,
+        guild_id as i64,
+        limit,
+        *RETENTION_DAYS
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| SnapshotInfo {
+            id: r.id,
+            reason: r.reason,
+            created_at: r.created_at,
+        })
+        .collect())
+}
+
+/// The guild's data as stored in snapshot `id`, `None` if it's gone or belongs elsewhere
+pub async fn load_snapshot(
+    pool: &PgPool,
+    guild_id: u64,
+    id: i64,
+) -> Result<Option<GuildData>, sqlx::Error> {
+    let _timer = db_timer("load_snapshot");
+    let row = sqlx::query!(
+        r#"
+        select data
+        from public.guild_snapshots
+        where guild_id = $1 and id = $2 and created_at >= now() - make_interval(days => $3)
+        "#,
+        guild_id as i64,
+        id,
+        *RETENTION_DAYS
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.and_then(|r| serde_json::from_value(r.data).ok()))
+}
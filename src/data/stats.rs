@@ -0,0 +1,168 @@
+use super::structs::CountMode;
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DayCount {
+    pub date: String,
+    pub count: i64,
+    pub increment: i64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct GuildStats {
+    pub total: i64, // counts made across every logged day
+    pub days_logged: usize,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    pub best_day: Option<DayCount>,
+    pub average_per_day: f64,
+    pub current_streak: usize, // consecutive days ending at last_date
+}
+
+/// Daily counts within `from..=to` (dates as `YYYY-MM-DD`), each with how far the count got
+/// since the previous logged day (even if that day is outside the range)
+pub fn day_counts(
+    daily_counts: &BTreeMap<String, i64>,
+    mode: CountMode,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Vec<DayCount> {
+    let mut prev_count = from.and_then(|from| {
+        daily_counts
+            .range(..from.to_string())
+            .next_back()
+            .map(|(_, v)| *v)
+    });
+
+    daily_counts
+        .iter()
+        .filter(|(date, _)| from.is_none_or(|from| date.as_str() >= from))
+        .filter(|(date, _)| to.is_none_or(|to| date.as_str() <= to))
+        .map(|(date, count)| {
+            let increment = progress(mode, prev_count, *count);
+            prev_count = Some(*count);
+            DayCount {
+                date: date.clone(),
+                count: *count,
+                increment,
+            }
+        })
+        .collect()
+}
+
+// Counts made to get from `prev` to `count`, a count that went the wrong way was restarted
+fn progress(mode: CountMode, prev: Option<i64>, count: i64) -> i64 {
+    match mode {
+        // counting down starts from any number, without a previous day it's unknown
+        CountMode::Backwards => prev.map_or(0, |prev| (prev - count).max(0)),
+        _ => {
+            let prev = prev.unwrap_or(0);
+            if count >= prev { count - prev } else { count }
+        }
+    }
+}
+
+pub fn guild_stats(daily_counts: &BTreeMap<String, i64>, mode: CountMode) -> GuildStats {
+    let days = day_counts(daily_counts, mode, None, None);
+    let Some(last) = days.last() else {
+        return GuildStats::default();
+    };
+
+    let dates: Vec<NaiveDate> = days
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(&d.date, "%Y-%m-%d").ok())
+        .collect();
+    let current_streak = 1 + dates
+        .windows(2)
+        .rev()
+        .take_while(|pair| (pair[1] - pair[0]).num_days() == 1)
+        .count();
+
+    let total = days.iter().map(|d| d.increment).sum();
+    GuildStats {
+        total,
+        days_logged: days.len(),
+        first_date: days.first().map(|d| d.date.clone()),
+        last_date: Some(last.date.clone()),
+        best_day: days.iter().max_by_key(|d| d.increment).cloned(),
+        average_per_day: total as f64 / days.len() as f64,
+        current_streak,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(days: &[(&str, i64)]) -> BTreeMap<String, i64> {
+        days.iter().map(|(d, c)| (d.to_string(), *c)).collect()
+    }
+
+    fn increments(days: &[DayCount]) -> Vec<i64> {
+        days.iter().map(|d| d.increment).collect()
+    }
+
+    #[test]
+    fn day_counts_go_up_from_the_previous_day() {
+        let daily = counts(&[("2024-01-01", 10), ("2024-01-02", 25), ("2024-01-04", 30)]);
+        assert_eq!(
+            increments(&day_counts(&daily, CountMode::Normal, None, None)),
+            [10, 15, 5]
+        );
+
+        // the day before the range still counts as the previous one
+        let days = day_counts(
+            &daily,
+            CountMode::Normal,
+            Some("2024-01-02"),
+            Some("2024-01-03"),
+        );
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, "2024-01-02");
+        assert_eq!(days[0].increment, 15);
+    }
+
+    #[test]
+    fn restarts_count_from_zero() {
+        let daily = counts(&[("2024-01-01", 50), ("2024-01-02", 8)]);
+        assert_eq!(
+            increments(&day_counts(&daily, CountMode::Normal, None, None)),
+            [50, 8]
+        );
+    }
+
+    #[test]
+    fn backwards_days_count_down() {
+        let daily = counts(&[("2024-01-01", 90), ("2024-01-02", 70), ("2024-01-03", 100)]);
+        // where the first day started and the run after the restart are unknown
+        assert_eq!(
+            increments(&day_counts(&daily, CountMode::Backwards, None, None)),
+            [0, 20, 0]
+        );
+    }
+
+    #[test]
+    fn guild_stats_sum_the_days() {
+        let daily = counts(&[("2024-01-01", 10), ("2024-01-03", 40), ("2024-01-04", 45)]);
+        let stats = guild_stats(&daily, CountMode::Normal);
+        assert_eq!(stats.total, 45);
+        assert_eq!(stats.days_logged, 3);
+        assert_eq!(stats.first_date.as_deref(), Some("2024-01-01"));
+        assert_eq!(stats.last_date.as_deref(), Some("2024-01-04"));
+        assert_eq!(
+            stats.best_day.map(|d| d.date),
+            Some("2024-01-03".to_string())
+        );
+        assert_eq!(stats.average_per_day, 15.0);
+        assert_eq!(stats.current_streak, 2);
+
+        let backwards = counts(&[("2024-01-01", 100), ("2024-01-02", 60), ("2024-01-03", 55)]);
+        let stats = guild_stats(&backwards, CountMode::Backwards);
+        assert_eq!(stats.total, 45);
+        assert_eq!(stats.best_day.map(|d| d.increment), Some(40));
+
+        assert_eq!(guild_stats(&BTreeMap::new(), CountMode::Normal).total, 0);
+    }
+}
@@ -1,6 +1,5 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GuildSettings {
@@ -8,6 +7,12 @@ pub struct GuildSettings {
     pub lang: String,
     pub lang2: Option<String>,
     pub auto_relog: bool,
+    pub relog_interval: i32, // minutes
+    pub public_api: bool,
+    pub webhooks: Vec<Webhook>,
+    pub role_rewards: Vec<RoleReward>,
+    pub access: AccessControl,
+    pub audit_channel_id: Option<u64>,
 }
 impl Default for GuildSettings {
     fn default() -> Self {
@@ -16,10 +21,116 @@ impl Default for GuildSettings {
             lang: "en".to_string(),
             lang2: None,
             auto_relog: true,
+            relog_interval: 5,
+            public_api: false,
+            webhooks: Vec::new(),
+            role_rewards: Vec::new(),
+            access: AccessControl::default(),
+            audit_channel_id: None,
         }
     }
 }
 
+/// Who besides Administrators may run the admin commands
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AccessControl {
+    #[serde(default)]
+    pub manager_roles: Vec<u64>, // may run every admin command without an override
+    #[serde(default)]
+    pub overrides: BTreeMap<String, Vec<u64>>, // "relog formatonly" -> roles allowed, replaces manager_roles
+}
+
+impl AccessControl {
+    /// Roles that may run `path` (e.g. `relog auto toggle`), the most specific override wins
+    pub fn allowed_roles(&self, path: &str) -> &[u64] {
+        let mut key = path;
+        loop {
+            if let Some(roles) = self.overrides.get(key) {
+                return roles;
+            }
+            match key.rfind(' ') {
+                Some(i) => key = &key[..i],
+                None => return &self.manager_roles,
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Milestone,
+    CountReset,
+    RelogCompleted,
+    RelogFailed,
+    InvalidCount,
+}
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 5] = [
+        WebhookEvent::Milestone,
+        WebhookEvent::CountReset,
+        WebhookEvent::RelogCompleted,
+        WebhookEvent::RelogFailed,
+        WebhookEvent::InvalidCount,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::Milestone => "milestone",
+            WebhookEvent::CountReset => "count_reset",
+            WebhookEvent::RelogCompleted => "relog_completed",
+            WebhookEvent::RelogFailed => "relog_failed",
+            WebhookEvent::InvalidCount => "invalid_count",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    pub secret: String,                 // HMAC-SHA256 key for the signature header
+    pub events: BTreeSet<WebhookEvent>, // empty = every event
+    pub milestone_every: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardKind {
+    LifetimeCounts, // reached `threshold` valid counts in total
+    Milestone,      // sent the count equal to `threshold`
+    MonthlyTop,     // most valid counts this month
+}
+impl RewardKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RewardKind::LifetimeCounts => "lifetime_counts",
+            RewardKind::Milestone => "milestone",
+            RewardKind::MonthlyTop => "monthly_top",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            RewardKind::LifetimeCounts,
+            RewardKind::Milestone,
+            RewardKind::MonthlyTop,
+        ]
+        .into_iter()
+        .find(|k| k.name() == name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RoleReward {
+    pub role_id: u64,
+    pub kind: RewardKind,
+    pub threshold: i64, // unused for monthly_top
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct IDs {
     pub log_channel_id: Option<u64>,
@@ -29,43 +140,186 @@ pub struct IDs {
     pub log_helper_msg_id: Option<u64>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CountMode {
+    #[default]
+    Normal, // 1, 2, 3...
+    Backwards, // 100, 99, 98...
+    Binary,    // 1, 10, 11...
+}
+impl CountMode {
+    pub const ALL: [CountMode; 3] = [CountMode::Normal, CountMode::Backwards, CountMode::Binary];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CountMode::Normal => "normal",
+            CountMode::Backwards => "backwards",
+            CountMode::Binary => "binary",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name() == name)
+    }
+
+    /// The number a counting message stands for, `None` if it isn't a count
+    pub fn parse(&self, content: &str) -> Option<i64> {
+        match self {
+            CountMode::Binary => i64::from_str_radix(content, 2).ok(),
+            _ => content.parse().ok(),
+        }
+    }
+
+    pub fn is_valid(&self, pre: i64, post: i64) -> bool {
+        match self {
+            CountMode::Backwards => pre.checked_sub(1) == Some(post),
+            _ => pre.checked_add(1) == Some(post),
+        }
+    }
+
+    /// Whether `num` starts the count over after `last`: back to 1 (or 0),
+    /// or any step up when counting backwards
+    pub fn is_restart(&self, last: i64, num: i64) -> bool {
+        match self {
+            CountMode::Backwards => num > last,
+            _ => last > 1 && num <= 1,
+        }
+    }
+
+    /// Where the count stands once `num` was read after `last`: valid counts and restarts move it,
+    /// a wrong number doesn't, so the next count is still checked against `last`
+    pub fn advance(&self, last: Option<i64>, num: i64) -> Option<i64> {
+        match last {
+            Some(last) if !self.is_valid(last, num) && !self.is_restart(last, num) => Some(last),
+            _ => Some(num),
+        }
+    }
+
+    /// Whichever of two counts got further, used to keep one count per day
+    pub fn furthest(&self, a: i64, b: i64) -> i64 {
+        match self {
+            CountMode::Backwards => a.min(b),
+            _ => a.max(b),
+        }
+    }
+
+    /// A count written the way it's counted in the channel
+    pub fn format(&self, count: i64) -> String {
+        match self {
+            CountMode::Binary => format!("{count:b}"),
+            _ => count.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CountingChannel {
+    #[serde(default)]
+    pub mode: CountMode,
+    pub ids: IDs,
+    pub daily_counts: BTreeMap<String, i64>,
+    #[serde(default)]
+    pub last_count: Option<i64>, // where the count stands, see `CountMode::advance`
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct GuildData {
     pub is_setup: bool,
     pub settings: GuildSettings,
-    pub ids: IDs,
-    pub daily_counts: BTreeMap<String, i64>,
+    pub channels: Vec<CountingChannel>, // the first one is the primary channel
 }
 
 impl GuildData {
     pub fn is_default_setup(&self) -> bool {
-        self.settings.utc == i8::default()
-            && self.ids.log_channel_id == Option::default()
-            && self.ids.counting_channel_id == Option::default()
+        self.settings.utc == i8::default() && self.channels.is_empty()
+    }
+
+    pub fn primary(&self) -> Option<&CountingChannel> {
+        self.channels.first()
+    }
+
+    /// Daily counts of the primary channel, empty if there is none
+    pub fn primary_counts(&self) -> &BTreeMap<String, i64> {
+        static EMPTY: BTreeMap<String, i64> = BTreeMap::new();
+        self.primary().map_or(&EMPTY, |c| &c.daily_counts)
+    }
+
+    /// Count mode of the primary channel, the default one if there is none
+    pub fn primary_mode(&self) -> CountMode {
+        self.primary().map_or_else(CountMode::default, |c| c.mode)
+    }
+
+    pub fn channel(&self, counting_channel_id: u64) -> Option<&CountingChannel> {
+        self.channels
+            .iter()
+            .find(|c| c.ids.counting_channel_id == Some(counting_channel_id))
+    }
+
+    pub fn channel_mut(&mut self, counting_channel_id: u64) -> Option<&mut CountingChannel> {
+        self.channels
+            .iter_mut()
+            .find(|c| c.ids.counting_channel_id == Some(counting_channel_id))
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct AllGuildData(pub HashMap<u64, GuildData>);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GuildRow {
-    pub guild_id: i64,
-    pub is_setup: bool,
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // settings
-    pub utc: i16,
-    pub lang: String,
-    pub lang2: Option<String>,
-    pub auto_relog: bool,
+    #[test]
+    fn parse_reads_each_mode() {
+        assert_eq!(CountMode::Normal.parse("42"), Some(42));
+        assert_eq!(CountMode::Backwards.parse("-3"), Some(-3));
+        assert_eq!(CountMode::Binary.parse("101"), Some(5));
+        assert_eq!(CountMode::Binary.parse("12"), None);
+        assert_eq!(CountMode::Normal.parse("42!"), None);
+        assert_eq!(CountMode::Normal.parse(""), None);
+    }
+
+    #[test]
+    fn is_valid_follows_the_direction() {
+        assert!(CountMode::Normal.is_valid(41, 42));
+        assert!(!CountMode::Normal.is_valid(41, 43));
+        assert!(CountMode::Binary.is_valid(5, 6));
+        assert!(CountMode::Backwards.is_valid(1, 0));
+        assert!(!CountMode::Backwards.is_valid(1, 2));
+    }
 
-    // ids
-    pub log_channel_id: Option<i64>,
-    pub counting_channel_id: Option<i64>,
-    pub log_msg_map: Value,
-    pub last_scanned_msg_id: Option<i64>,
-    pub log_helper_msg_id: Option<i64>,
+    #[test]
+    fn is_valid_does_not_overflow() {
+        assert!(!CountMode::Normal.is_valid(i64::MAX, i64::MIN));
+        assert!(!CountMode::Backwards.is_valid(i64::MIN, i64::MAX));
+    }
 
-    // maps
-    pub daily_counts: Value,
+    #[test]
+    fn wrong_numbers_do_not_rebase_the_count() {
+        let mode = CountMode::Normal;
+        assert_eq!(mode.advance(None, 7), Some(7));
+        assert_eq!(mode.advance(Some(41), 42), Some(42));
+        assert_eq!(mode.advance(Some(41), 50), Some(41));
+        assert_eq!(mode.advance(Some(41), 1), Some(1)); // restart
+        assert_eq!(CountMode::Backwards.advance(Some(10), 3), Some(10));
+        assert_eq!(CountMode::Backwards.advance(Some(0), 100), Some(100)); // new run
+    }
+
+    #[test]
+    fn allowed_roles_picks_the_most_specific_override() {
+        let access = AccessControl {
+            manager_roles: vec![1],
+            overrides: BTreeMap::from([
+                ("relog".to_string(), vec![2]),
+                ("relog auto toggle".to_string(), vec![3]),
+            ]),
+        };
+        assert_eq!(access.allowed_roles("relog auto toggle"), &[3]);
+        assert_eq!(access.allowed_roles("relog auto interval"), &[2]);
+        assert_eq!(access.allowed_roles("relog"), &[2]);
+        assert_eq!(access.allowed_roles("setup channel"), &[1]);
+        // prefixes only match whole words
+        assert_eq!(access.allowed_roles("relogged"), &[1]);
+    }
 }
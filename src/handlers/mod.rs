@@ -1,11 +1,18 @@
 pub mod ready;
 
-use crate::data::{BotData, load_guild_data, save_guild_data};
+use crate::commands::relog::{message_day, recount_days};
+use crate::data::structs::{CountMode, GuildData, Webhook, WebhookEvent};
+use crate::data::{BotData, load_guild_data, lock_guild, save_guild_data};
+use crate::utils::log_error;
 use chrono::*;
-use serenity::all::{ActivityData, Message};
+use serde_json::json;
+use serenity::all::{
+    ActivityData, ChannelId, GuildChannel, GuildId, Message, MessageId, MessageUpdateEvent,
+};
 use serenity::async_trait;
 use serenity::model::prelude::Interaction;
 use serenity::prelude::*;
+use std::collections::BTreeSet;
 
 pub struct Handler {
     pub bot_data: std::sync::Arc<BotData>,
@@ -15,6 +22,133 @@ impl Handler {
     pub fn new(bot_data: std::sync::Arc<BotData>) -> Self {
         Self { bot_data }
     }
+
+    // Store a live count as today's total
+    #[tracing::instrument(name = "message", skip_all, fields(guild_id = guild_id_u64))]
+    async fn record_count(&self, ctx: &Context, guild_id_u64: u64, msg: &Message) {
+        let mut modified = false;
+        let mut rewarded = None;
+        // messages are handled concurrently, two quick counts must not both check against
+        // the same last count or save over each other
+        let guard = lock_guild(guild_id_u64).await;
+        match load_guild_data(&self.bot_data.pool, guild_id_u64).await {
+            Ok(mut guild_data) => {
+                let key = message_day(msg.id, guild_data.settings.utc)
+                    .format("%Y-%m-%d")
+                    .to_string();
+                let GuildData {
+                    settings, channels, ..
+                } = &mut guild_data;
+                if let Some(channel) = channels
+                    .iter_mut()
+                    .find(|c| c.ids.counting_channel_id == Some(msg.channel_id.get()))
+                    && let Some(num) = channel.mode.parse(&msg.content)
+                {
+                    // channels saved before last_count existed fall back to the latest day
+                    let last = channel
+                        .last_count
+                        .or_else(|| channel.daily_counts.values().next_back().copied());
+                    let valid = last.is_none_or(|last| channel.mode.is_valid(last, num));
+                    if valid {
+                        rewarded = Some((num, key[..7].to_string()));
+                        let mode = channel.mode;
+                        channel
+                            .daily_counts
+                            .entry(key)
+                            .and_modify(|c| *c = mode.furthest(*c, num))
+                            .or_insert(num);
+                        // a valid successor means nothing countable was missed in between,
+                        // otherwise the poller rescans from the previous position
+                        if channel
+                            .ids
+                            .last_scanned_msg_id
+                            .is_none_or(|id| id < msg.id.get())
+                        {
+                            channel.ids.last_scanned_msg_id = Some(msg.id.get());
+                        }
+                    }
+                    channel.last_count = channel.mode.advance(last, num);
+                    modified = true;
+                    crate::metrics::counting_messages("live", 1);
+                    notify_count(
+                        guild_id_u64,
+                        &settings.webhooks,
+                        channel.mode,
+                        last,
+                        num,
+                        msg,
+                    );
+                }
+
+                if !modified {
+                    return;
+                }
+                let _ = save_guild_data(&self.bot_data.pool, guild_id_u64, &guild_data).await;
+                drop(guard);
+                crate::scheduler::request_update(guild_id_u64).await;
+
+                if let Some((num, month)) = rewarded {
+                    crate::rewards::on_valid_count(
+                        ctx,
+                        &self.bot_data.pool,
+                        guild_id_u64,
+                        &guild_data.settings.role_rewards,
+                        msg.author.id.get(),
+                        num,
+                        &month,
+                    )
+                    .await;
+                }
+            }
+            Err(e) => log_error(&format!(
+                "❌ Cannot load data from Guild{guild_id_u64}: {e}"
+            )),
+        }
+    }
+
+    // Recount the days of edited/deleted counting messages and refresh their logs
+    #[tracing::instrument(name = "recount", skip_all, fields(guild_id = guild_id.map(|id| id.get())))]
+    async fn recount_messages(
+        &self,
+        ctx: &Context,
+        guild_id: Option<GuildId>,
+        channel_id: ChannelId,
+        message_ids: &[MessageId],
+    ) {
+        let Some(guild_id) = guild_id else {
+            return;
+        };
+        let guild_id_u64 = guild_id.get();
+
+        let utc = match load_guild_data(&self.bot_data.pool, guild_id_u64).await {
+            Ok(guild_data) if guild_data.channel(channel_id.get()).is_some() => {
+                guild_data.settings.utc
+            }
+            Ok(_) => return,
+            Err(e) => {
+                log_error(&format!(
+                    "❌ Cannot load data from Guild{guild_id_u64}: {e}"
+                ));
+                return;
+            }
+        };
+
+        let days: BTreeSet<NaiveDate> =
+            message_ids.iter().map(|id| message_day(*id, utc)).collect();
+
+        match recount_days(&ctx.http, &self.bot_data, guild_id_u64, channel_id, &days).await {
+            Ok(years) if !years.is_empty() => {
+                crate::scheduler::request_refresh(guild_id_u64, years).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                crate::metrics::discord_error(e.as_ref());
+                log_error(&format!(
+                    "Failed recounting edited messages for Guild{guild_id_u64}: {e}"
+                ))
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -35,6 +169,8 @@ impl EventHandler for Handler {
                 crate::commands::setup::register(),
                 crate::commands::relog::register(),
                 crate::commands::message::register(),
+                crate::commands::webhook::register(),
+                crate::commands::reward::register(),
             ],
         )
         .await;
@@ -45,49 +181,97 @@ impl EventHandler for Handler {
 
         // Others
         let bot_data = std::sync::Arc::clone(&self.bot_data);
-        tokio::spawn(crate::commands::relog::log_daily_counts(ctx, bot_data));
+        crate::scheduler::on_ready(ctx, bot_data).await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            crate::commands::handle(ctx, command, &self.bot_data).await;
+        match interaction {
+            Interaction::Command(command) => {
+                crate::commands::handle(ctx, command, &self.bot_data).await;
+            }
+            Interaction::Component(component) => {
+                crate::components::dispatch(ctx, component).await;
+            }
+            _ => {}
         }
     }
 
+    async fn thread_update(&self, ctx: Context, _old: Option<GuildChannel>, new: GuildChannel) {
+        crate::threads::on_thread_update(&ctx, &self.bot_data, &new).await;
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _old_if_available: Option<Message>,
+        _new: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        // embed-only updates (link previews etc.) don't carry content
+        if event.content.is_none() || event.author.as_ref().is_some_and(|a| a.bot) {
+            return;
+        }
+
+        self.recount_messages(&ctx, event.guild_id, event.channel_id, &[event.id])
+            .await;
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        self.recount_messages(&ctx, guild_id, channel_id, &[deleted_message_id])
+            .await;
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        self.recount_messages(&ctx, guild_id, channel_id, &multiple_deleted_messages_ids)
+            .await;
+    }
+
     async fn message(&self, ctx: Context, msg: Message) {
         if msg.author.bot {
             return;
         }
 
         if let Some(guild_id) = msg.guild_id {
-            let mut modified = false;
-            let guild_id_u64 = guild_id.get();
-            match load_guild_data(&self.bot_data.pool, guild_id_u64).await {
-                Ok(mut guild_data) => {
-                    if let Some(_count_ch_id) = guild_data.ids.counting_channel_id {
-                        if msg.content.parse::<i64>().is_ok() {
-                            let key = get_current_time(guild_data.settings.utc);
-                            if let Ok(num) = msg.content.parse::<i64>() {
-                                guild_data.daily_counts.insert(key, num);
-                                modified = true;
-                            }
-                        }
-                    }
-
-                    if !modified {
-                        return;
-                    }
-                    let _ = save_guild_data(&self.bot_data.pool, guild_id_u64, &guild_data).await;
-                }
-                Err(e) => eprintln!("❌ Cannot load data from Guild{guild_id_u64}: {e}"),
-            }
+            self.record_count(&ctx, guild_id.get(), &msg).await;
         }
     }
 }
 
-fn get_current_time(utc: i8) -> String {
-    let utc_now: DateTime<Utc> = Utc::now();
-    let offset_now =
-        utc_now.with_timezone(&chrono::FixedOffset::east_opt(utc as i32 * 3600).unwrap());
-    offset_now.format("%Y-%m-%d").to_string()
+// Webhook events for a live count, compared to the latest logged total
+fn notify_count(
+    guild_id_u64: u64,
+    webhooks: &[Webhook],
+    mode: CountMode,
+    last: Option<i64>,
+    num: i64,
+    msg: &Message,
+) {
+    let data = json!({
+        "count": num,
+        "previous": last,
+        "user_id": msg.author.id.to_string(),
+        "channel_id": msg.channel_id.to_string(),
+        "message_id": msg.id.to_string(),
+    });
+
+    if last.is_some_and(|last| mode.is_restart(last, num)) {
+        crate::webhooks::emit(guild_id_u64, webhooks, WebhookEvent::CountReset, data);
+    } else if last.is_some_and(|last| !mode.is_valid(last, num)) {
+        crate::metrics::invalid_counts("live", 1);
+        crate::webhooks::emit(guild_id_u64, webhooks, WebhookEvent::InvalidCount, data);
+    } else {
+        crate::webhooks::emit_milestone(guild_id_u64, webhooks, num, data);
+    }
 }
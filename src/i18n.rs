@@ -0,0 +1,279 @@
+use crate::utils::{log_error, log_info, log_warn};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::{env, fs};
+
+/// Language every pack is checked against and every lookup falls back to
+pub const DEFAULT_LANG: &str = "en";
+
+/// Choices Discord allows on a command option, `/setup language` offers no more packs than that
+pub const MAX_CHOICES: usize = 25;
+
+// Packs built into the binary, `<code>.json` files in `LANG_PACK_DIR` add to or override them
+const EMBEDDED: [(&str, &str); 2] = [
+    ("en", include_str!("lang/en.json")),
+    ("jp", include_str!("lang/jp.json")),
+];
+
+// Locales Discord accepts for command localisations
+const DISCORD_LOCALES: [&str; 32] = [
+    "id", "da", "de", "en-GB", "en-US", "es-ES", "es-419", "fr", "hr", "it", "lt", "hu", "nl",
+    "no", "pl", "pt-BR", "ro", "fi", "sv-SE", "vi", "tr", "cs", "el", "bg", "ru", "uk", "hi", "th",
+    "zh-CN", "ja", "zh-TW", "ko",
+];
+
+// Every field is optional so an override can patch a few words only
+#[derive(Deserialize)]
+struct PackFile {
+    name: Option<String>,
+    locale: Option<String>,
+    #[serde(default)]
+    words: HashMap<String, String>,
+}
+
+pub struct Pack {
+    /// Native name of the language, offered by `/setup language`
+    pub name: String,
+    /// Discord locale served by the pack, e.g. "ja"
+    pub locale: Option<String>,
+    words: HashMap<String, String>,
+}
+
+static PACKS: Lazy<BTreeMap<String, Pack>> = Lazy::new(|| {
+    let mut packs = BTreeMap::new();
+    for (code, data) in EMBEDDED {
+        let file = serde_json::from_str(data)
+            .unwrap_or_else(|e| panic!("❌ Failed to parse language pack '{code}': {e}"));
+        merge(&mut packs, code, file);
+    }
+
+    if let Ok(dir) = env::var("LANG_PACK_DIR") {
+        match fs::read_dir(&dir) {
+            Ok(entries) => {
+                let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+                paths.sort();
+                for path in paths {
+                    load_override(&mut packs, &path);
+                }
+            }
+            Err(e) => log_error(&format!("Failed to read language packs from '{dir}': {e}")),
+        }
+    }
+
+    packs
+});
+
+fn load_override(packs: &mut BTreeMap<String, Pack>, path: &Path) {
+    if path.extension().is_none_or(|ext| ext != "json") {
+        return;
+    }
+    let Some(code) = path.file_stem().and_then(|s| s.to_str()) else {
+        return;
+    };
+
+    let file = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_str(&data).map_err(|e| e.to_string()))
+    {
+        Ok(file) => file,
+        Err(e) => {
+            log_error(&format!("Skipped language pack '{}': {e}", path.display()));
+            return;
+        }
+    };
+    merge(packs, code, file);
+}
+
+fn merge(packs: &mut BTreeMap<String, Pack>, code: &str, file: PackFile) {
+    let pack = packs.entry(code.to_string()).or_insert_with(|| Pack {
+        name: code.to_string(),
+        locale: None,
+        words: HashMap::new(),
+    });
+    if let Some(name) = file.name {
+        pack.name = name;
+    }
+    if let Some(locale) = file.locale {
+        pack.locale = Some(locale);
+    }
+    // keys are looked up uppercased
+    pack.words.extend(
+        file.words
+            .into_iter()
+            .map(|(key, text)| (key.to_uppercase(), text)),
+    );
+}
+
+/// Every available language by code, sorted
+pub fn languages() -> impl Iterator<Item = (&'static str, &'static Pack)> {
+    PACKS.iter().map(|(code, pack)| (code.as_str(), pack))
+}
+
+pub fn is_available(lang: &str) -> bool {
+    PACKS.contains_key(lang)
+}
+
+/// Text of `key` in `lang`, in the default language when the pack lacks it
+pub fn word(key: &str, lang: &str) -> Option<&'static str> {
+    let key = key.to_uppercase();
+    [lang, DEFAULT_LANG]
+        .into_iter()
+        .find_map(|lang| PACKS.get(lang)?.words.get(&key))
+        .map(|text| text.as_str())
+}
+
+/// Language serving a Discord locale, "en-GB" is served by a pack for "en-US" too
+pub fn from_locale(locale: &str) -> Option<&'static str> {
+    let primary = |locale: &str| locale.split('-').next().unwrap_or_default().to_string();
+    languages()
+        .find(|(_, pack)| pack.locale.as_deref() == Some(locale))
+        .or_else(|| {
+            languages()
+                .find(|(_, pack)| pack.locale.as_deref().map(primary) == Some(primary(locale)))
+        })
+        .map(|(code, _)| code)
+}
+
+/// Discord localisations of `key` by every pack besides the default one
+pub fn localizations(key: &str) -> Vec<(String, String)> {
+    let key = key.to_uppercase();
+    languages()
+        .filter(|(code, _)| *code != DEFAULT_LANG)
+        .filter_map(|(_, pack)| {
+            let locale = pack
+                .locale
+                .as_deref()
+                .filter(|l| DISCORD_LOCALES.contains(l))?;
+            Some((locale.to_string(), pack.words.get(&key)?.clone()))
+        })
+        .collect()
+}
+
+/// Loads the packs and reports the keys they miss or don't know compared to the default one
+pub fn check() {
+    log_info(&format!(
+        "🌐 Language packs: {}",
+        languages()
+            .map(|(code, _)| code)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    let count = languages().count();
+    if count > MAX_CHOICES {
+        log_warn(&format!(
+            "🌐 {count} language packs are loaded, `/setup language` only offers the first {MAX_CHOICES}, left out: {}",
+            languages()
+                .skip(MAX_CHOICES)
+                .map(|(code, _)| code)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let Some(default) = PACKS.get(DEFAULT_LANG) else {
+        log_error(&format!(
+            "Default language pack '{DEFAULT_LANG}' is missing"
+        ));
+        return;
+    };
+    for (code, pack) in languages().filter(|(code, _)| *code != DEFAULT_LANG) {
+        let mut missing: Vec<_> = default
+            .words
+            .keys()
+            .filter(|k| !pack.words.contains_key(*k))
+            .collect();
+        let mut unknown: Vec<_> = pack
+            .words
+            .keys()
+            .filter(|k| !default.words.contains_key(*k))
+            .collect();
+        missing.sort();
+        unknown.sort();
+
+        if !missing.is_empty() {
+            log_warn(&format!(
+                "🌐 Language pack '{code}' is missing {} keys, they fall back to '{DEFAULT_LANG}': {}",
+                missing.len(),
+                missing
+                    .iter()
+                    .map(|k| k.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !unknown.is_empty() {
+            log_warn(&format!(
+                "🌐 Language pack '{code}' has {} unused keys: {}",
+                unknown.len(),
+                unknown
+                    .iter()
+                    .map(|k| k.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        match pack.locale.as_deref() {
+            Some(locale) if !DISCORD_LOCALES.contains(&locale) => log_warn(&format!(
+                "🌐 Language pack '{code}' has locale '{locale}' that Discord doesn't know, its command descriptions aren't localised"
+            )),
+            None => log_warn(&format!(
+                "🌐 Language pack '{code}' has no locale, it's only used through `/setup language`"
+            )),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack_file(name: Option<&str>, words: &[(&str, &str)]) -> PackFile {
+        PackFile {
+            name: name.map(str::to_string),
+            locale: None,
+            words: words
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn merge_patches_an_existing_pack() {
+        let mut packs = BTreeMap::new();
+        merge(
+            &mut packs,
+            "en",
+            pack_file(Some("English"), &[("HELLO", "Hello"), ("BYE", "Bye")]),
+        );
+        merge(&mut packs, "en", pack_file(None, &[("bye", "Goodbye")]));
+
+        let pack = &packs["en"];
+        assert_eq!(pack.name, "English");
+        assert_eq!(pack.words["HELLO"], "Hello");
+        assert_eq!(pack.words["BYE"], "Goodbye");
+
+        // a new code without a name is named after itself
+        merge(&mut packs, "fr", pack_file(None, &[]));
+        assert_eq!(packs["fr"].name, "fr");
+    }
+
+    #[test]
+    fn from_locale_falls_back_to_the_primary_language() {
+        assert_eq!(from_locale("ja"), Some("jp"));
+        assert_eq!(from_locale("en-US"), Some("en"));
+        assert_eq!(from_locale("en-GB"), Some("en"));
+        assert_eq!(from_locale("ko"), None);
+    }
+
+    #[test]
+    fn localizations_skip_the_default_pack() {
+        let localized = localizations("desc_ping");
+        assert!(localized.iter().any(|(locale, _)| locale == "ja"));
+        assert!(localized.iter().all(|(locale, _)| locale != "en-US"));
+        assert!(localizations("NO_SUCH_KEY").is_empty());
+    }
+}
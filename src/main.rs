@@ -1,4 +1,3 @@
-use axum::{Router, routing::get};
 use dotenv::dotenv;
 use std::env;
 // use std::fs;
@@ -6,18 +5,36 @@ use std::env;
 // use std::process::exit;
 use tokio::net::TcpListener;
 
-// use crate::utils::log_error;
+use crate::utils::{log_error, log_info, log_warn};
 
+mod audit;
 mod bot;
 mod commands;
+mod components;
 mod data;
 mod handlers;
+mod i18n;
+mod metrics;
+mod permissions;
+mod rewards;
+mod scheduler;
+mod shutdown;
+mod threads;
 mod utils;
+mod web;
+mod webhooks;
 
 #[tokio::main]
 async fn main() {
-    env_logger::init();
-    println!("🚀 App booted at {:?}", std::time::SystemTime::now());
+    dotenv().ok();
+    utils::init_logging();
+    i18n::check();
+
+    let booted_at = std::time::Instant::now();
+    log_info(&format!(
+        "🚀 App booted at {:?}",
+        std::time::SystemTime::now()
+    ));
 
     // ===== DATA JSON =====
 
@@ -41,18 +58,16 @@ async fn main() {
     //     }
     // }
 
-    dotenv().ok();
-
     // ===== ENV CHECKS =====
     let token = env::var("DISCORD_TOKEN").expect("❌ DISCORD_TOKEN missing");
 
     let port = env::var("PORT").unwrap_or_else(|_| {
-        println!("⚠️ PORT not set, defaulting to 3000");
+        log_warn("⚠️ PORT not set, defaulting to 3000");
         "3000".to_string()
     });
 
-    println!("🔑 Discord token loaded");
-    println!("🌐 Web server port: {port}");
+    log_info("🔑 Discord token loaded");
+    log_info(&format!("🌐 Web server port: {port}"));
 
     let database_url = std::env::var("DATABASE_URL").expect("❌ DATABASE_URL not set");
 
@@ -60,8 +75,8 @@ async fn main() {
     let pool = loop {
         match sqlx::PgPool::connect(&database_url).await {
             Err(e) => {
-                eprintln!("❌ Couldn't connect to Database: {e}");
-                println!("🔁 Trying Database again in 10 seconds…");
+                log_error(&format!("❌ Couldn't connect to Database: {e}"));
+                log_info("🔁 Trying Database again in 10 seconds…");
                 tokio::time::sleep(std::time::Duration::from_secs(10)).await;
             }
             Ok(pool) => {
@@ -70,7 +85,12 @@ async fn main() {
                     .await
                     .unwrap();
 
-                println!("✅ DB OK: {:?}", row);
+                log_info(&format!("✅ DB OK: {:?}", row));
+
+                sqlx::migrate!("./migrations")
+                    .run(&pool)
+                    .await
+                    .expect("❌ Failed to run Database migrations");
 
                 sqlx::query("select * from public.guilds limit 1")
                     .execute(&pool)
@@ -83,26 +103,38 @@ async fn main() {
     };
 
     // ===== DISCORD BOT =====
-    tokio::spawn(async move {
-        if let Err(e) = bot::run(token, pool).await {
-            eprintln!("💀 Bot task exited unexpectedly: {e}");
+    let bot_pool = pool.clone();
+    let bot_task = tokio::spawn(async move {
+        if let Err(e) = bot::run(token, bot_pool).await {
+            log_error(&format!("💀 Bot task exited unexpectedly: {e}"));
         }
     });
 
-    // ===== WEB SERVER (Render keep-alive) =====
-    let app = Router::new()
-        .route("/", get(|| async { "📊 CountLogger Online 💙" }))
-        .route("/health", get(|| async { "ok" }));
+    // ===== WEB SERVER (Render keep-alive + public API) =====
+    let app = web::router(pool);
 
     let addr = format!("0.0.0.0:{port}");
     let listener = TcpListener::bind(&addr)
         .await
         .expect("❌ Failed to bind TCP listener");
 
-    println!("✅ Web server listening on http://{addr}");
+    log_info(&format!("✅ Web server listening on http://{addr}"));
 
-    // This should NEVER exit
+    // Only exits on SIGINT/SIGTERM
     axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown::wait_for_signal())
         .await
         .expect("❌ Axum server crashed");
+
+    log_info("🌐 Web server stopped");
+
+    // ===== SHUTDOWN =====
+    shutdown::run(booted_at).await;
+
+    if tokio::time::timeout(std::time::Duration::from_secs(10), bot_task)
+        .await
+        .is_err()
+    {
+        log_warn("⚠️ Bot task didn't stop in time");
+    }
 }
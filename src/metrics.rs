@@ -0,0 +1,215 @@
+use once_cell::sync::Lazy;
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use serenity::all::HttpError;
+use std::error::Error;
+
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+fn register<T: Collector + Clone + 'static>(metric: T) -> T {
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("❌ Failed to register metric");
+    metric
+}
+
+// `source` is where the message was read: live (gateway), scan (auto relog) or relog (full /relog)
+static COUNTING_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "countlogger_counting_messages_total",
+                "Counting messages processed",
+            ),
+            &["source"],
+        )
+        .unwrap(),
+    )
+});
+
+static INVALID_COUNTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "countlogger_invalid_counts_total",
+                "Counts rejected for not following the previous one",
+            ),
+            &["source"],
+        )
+        .unwrap(),
+    )
+});
+
+static DB_QUERY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "countlogger_db_query_duration_seconds",
+                "Database query latency",
+            )
+            .buckets(vec![
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+            ]),
+            &["query"],
+        )
+        .unwrap(),
+    )
+});
+
+static DISCORD_HTTP_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "countlogger_discord_http_errors_total",
+                "Failed Discord HTTP requests",
+            ),
+            &["route", "status"],
+        )
+        .unwrap(),
+    )
+});
+
+static AUTO_RELOG_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "countlogger_auto_relog_duration_seconds",
+                "Time spent on one guild's auto relog tick",
+            )
+            .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0]),
+        )
+        .unwrap(),
+    )
+});
+
+static RELOG_SESSIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register(
+        IntGauge::new(
+            "countlogger_relog_sessions_running",
+            "Relog sessions currently running",
+        )
+        .unwrap(),
+    )
+});
+
+static GUILDS_CONFIGURED: Lazy<IntGauge> = Lazy::new(|| {
+    register(
+        IntGauge::new(
+            "countlogger_guilds_configured",
+            "Guilds that finished /setup",
+        )
+        .unwrap(),
+    )
+});
+
+pub fn counting_messages(source: &str, amount: u64) {
+    COUNTING_MESSAGES
+        .with_label_values(&[source])
+        .inc_by(amount);
+}
+
+pub fn invalid_counts(source: &str, amount: u64) {
+    INVALID_COUNTS.with_label_values(&[source]).inc_by(amount);
+}
+
+/// Observes the query latency when dropped (or on `observe_duration`)
+pub fn db_timer(query: &str) -> HistogramTimer {
+    DB_QUERY_SECONDS.with_label_values(&[query]).start_timer()
+}
+
+pub fn auto_relog_timer() -> HistogramTimer {
+    AUTO_RELOG_SECONDS.start_timer()
+}
+
+pub fn guilds_configured(amount: usize) {
+    GUILDS_CONFIGURED.set(amount as i64);
+}
+
+/// Counts the error if it's a failed Discord request, anything else is ignored
+pub fn discord_error(err: &(dyn Error + 'static)) {
+    let Some(serenity::Error::Http(http_err)) = err.downcast_ref::<serenity::Error>() else {
+        return;
+    };
+
+    let (route, status) = match http_err {
+        HttpError::UnsuccessfulRequest(res) => (
+            format!("{} {}", res.method, route_of(&res.url)),
+            res.status_code.as_u16().to_string(),
+        ),
+        // never got a response (timeouts, connection resets...)
+        HttpError::Request(_) => ("unknown".to_string(), "none".to_string()),
+        _ => return,
+    };
+
+    DISCORD_HTTP_ERRORS
+        .with_label_values(&[&route, &status])
+        .inc();
+}
+
+// `https://discord.com/api/v10/channels/123/messages/456` -> `/channels/{id}/messages/{id}`
+// so the label doesn't explode with one series per message
+fn route_of(url: &str) -> String {
+    let path = url
+        .split_once("/api/")
+        .map_or(url, |(_, path)| path)
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+
+    path.split('/')
+        .filter(|part| !part.is_empty())
+        .skip_while(|part| is_api_version(part))
+        .map(|part| {
+            if part.chars().all(|c| c.is_ascii_digit()) {
+                "/{id}".to_string()
+            } else {
+                format!("/{part}")
+            }
+        })
+        .collect()
+}
+
+fn is_api_version(part: &str) -> bool {
+    part.strip_prefix('v')
+        .is_some_and(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Everything in the Prometheus text format
+pub async fn render() -> String {
+    // metrics nobody touched yet still show up as 0
+    Lazy::force(&COUNTING_MESSAGES);
+    Lazy::force(&INVALID_COUNTS);
+    Lazy::force(&DB_QUERY_SECONDS);
+    Lazy::force(&DISCORD_HTTP_ERRORS);
+    Lazy::force(&AUTO_RELOG_SECONDS);
+    Lazy::force(&GUILDS_CONFIGURED);
+    RELOG_SESSIONS.set(crate::commands::relog::running_relogs().await as i64);
+
+    let mut buffer = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer) {
+        crate::utils::log_error(&format!("Failed to encode metrics: {e}"));
+    }
+    String::from_utf8(buffer).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_of_hides_ids_and_query() {
+        assert_eq!(
+            route_of("https://discord.com/api/v10/channels/123456/messages?limit=100"),
+            "/channels/{id}/messages"
+        );
+        assert_eq!(
+            route_of("https://discord.com/api/v10/channels/1/messages/2/reactions#x"),
+            "/channels/{id}/messages/{id}/reactions"
+        );
+        assert_eq!(route_of("https://discord.com/api/gateway"), "/gateway");
+        assert_eq!(route_of("/users/@me"), "/users/@me");
+    }
+}
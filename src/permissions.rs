@@ -0,0 +1,107 @@
+use serenity::all::{ChannelId, GuildChannel, GuildId, Permissions};
+use serenity::prelude::Context;
+
+use crate::utils::tr;
+
+/// Needed to scan counts
+pub const COUNTING_PERMISSIONS: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::READ_MESSAGE_HISTORY);
+
+/// Needed to post, edit and clean up logs
+pub const LOG_PERMISSIONS: Permissions = Permissions::VIEW_CHANNEL
+    .union(Permissions::READ_MESSAGE_HISTORY)
+    .union(Permissions::SEND_MESSAGES)
+    .union(Permissions::EMBED_LINKS)
+    .union(Permissions::MANAGE_MESSAGES);
+
+async fn guild_channel(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<GuildChannel> {
+    if let Some(guild) = ctx.cache.guild(guild_id)
+        && let Some(channel) = guild
+            .channels
+            .get(&channel_id)
+            .or_else(|| guild.threads.iter().find(|t| t.id == channel_id))
+    {
+        return Some(channel.clone());
+    }
+    channel_id.to_channel(&ctx.http).await.ok()?.guild()
+}
+
+/// Which of `required` the bot lacks in the channel, `Err` (in `lang`) when it can't even be looked at
+pub async fn missing_permissions(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    required: Permissions,
+    lang: &str,
+) -> Result<Permissions, String> {
+    let Some(channel) = guild_channel(ctx, guild_id, channel_id).await else {
+        return Err(tr("PERM_CHANNEL_UNSEEN", lang, &[("channel", &channel_id)]));
+    };
+
+    // threads take their permissions from the parent, sending in them is its own permission
+    let (channel, required) = match channel.thread_metadata.and(channel.parent_id) {
+        Some(parent_id) => {
+            let Some(parent) = guild_channel(ctx, guild_id, parent_id).await else {
+                return Err(tr("PERM_PARENT_UNSEEN", lang, &[("channel", &channel_id)]));
+            };
+            let mut required = required;
+            if required.contains(Permissions::SEND_MESSAGES) {
+                required.remove(Permissions::SEND_MESSAGES);
+                required.insert(Permissions::SEND_MESSAGES_IN_THREADS);
+            }
+            (parent, required)
+        }
+        None => (channel, required),
+    };
+
+    let bot_id = ctx.cache.current_user().id;
+    let member = match guild_id.member(ctx, bot_id).await {
+        Ok(member) => member,
+        Err(e) => return Err(tr("PERM_MEMBER_FAILED", lang, &[("error", &e)])),
+    };
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return Err(tr("PERM_NOT_CACHED", lang, &[]));
+    };
+
+    Ok(required - guild.user_permissions_in(&channel, &member))
+}
+
+pub fn describe_missing(channel_id: ChannelId, missing: Permissions, lang: &str) -> String {
+    let names = missing
+        .get_permission_names()
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    tr(
+        "PERM_MISSING",
+        lang,
+        &[("channel", &channel_id), ("permissions", &names)],
+    )
+}
+
+/// One line per channel describing what's missing, empty when everything is granted
+pub async fn preflight(
+    ctx: &Context,
+    guild_id: GuildId,
+    log_channel: ChannelId,
+    counting_channel: ChannelId,
+    lang: &str,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (channel_id, required) in [
+        (counting_channel, COUNTING_PERMISSIONS),
+        (log_channel, LOG_PERMISSIONS),
+    ] {
+        match missing_permissions(ctx, guild_id, channel_id, required, lang).await {
+            Ok(missing) if missing.is_empty() => {}
+            Ok(missing) => problems.push(describe_missing(channel_id, missing, lang)),
+            Err(problem) => problems.push(problem),
+        }
+    }
+    problems
+}
@@ -0,0 +1,403 @@
+use crate::data::rewards::*;
+use crate::data::structs::{RewardKind, RoleReward};
+use crate::utils::{log_error, log_info, log_warn, tr};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serenity::all::{GuildId, HttpError, Permissions, RoleId, StatusCode, UserId};
+use serenity::prelude::Context;
+use sqlx::PgPool;
+use std::collections::{BTreeSet, HashMap};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+// A role the bot can't manage would otherwise warn on every single count
+const BLOCKED_WARN_EVERY: Duration = Duration::from_secs(3600);
+
+static BLOCKED_WARNED: Lazy<Mutex<HashMap<(u64, u64), Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Holders of every reward role by (guild, role), loaded once and kept in step with the table
+// so counts don't have to query them
+type Holders = HashMap<(u64, u64), BTreeSet<u64>>;
+static HOLDERS: Lazy<Mutex<Holders>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+async fn holders(pool: &PgPool, guild_id: u64, role_id: u64) -> Result<BTreeSet<u64>, sqlx::Error> {
+    if let Some(holders) = HOLDERS.lock().await.get(&(guild_id, role_id)) {
+        return Ok(holders.clone());
+    }
+    let holders: BTreeSet<u64> = reward_holders(pool, guild_id, role_id)
+        .await?
+        .into_iter()
+        .collect();
+    HOLDERS
+        .lock()
+        .await
+        .insert((guild_id, role_id), holders.clone());
+    Ok(holders)
+}
+
+// Month (`YYYY-MM`) each guild's monthly top roles were last checked for
+static CHECKED_MONTHS: Lazy<Mutex<HashMap<u64, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+async fn set_holder(guild_id: u64, role_id: u64, user_id: u64, holds: bool) {
+    // not loaded yet, the next load reads the table anyway
+    if let Some(holders) = HOLDERS.lock().await.get_mut(&(guild_id, role_id)) {
+        if holds {
+            holders.insert(user_id);
+        } else {
+            holders.remove(&user_id);
+        }
+    }
+}
+
+/// Why the bot can't assign `role_id` in the guild (in `lang`), `None` when it can
+pub async fn role_blocker(
+    ctx: &Context,
+    guild_id: GuildId,
+    role_id: RoleId,
+    lang: &str,
+) -> Option<String> {
+    let bot_id = ctx.cache.current_user().id;
+    let bot_member = match guild_id.member(ctx, bot_id).await {
+        Ok(member) => member,
+        Err(e) => return Some(tr("PERM_MEMBER_FAILED", lang, &[("error", &e)])),
+    };
+
+    // without the roles nothing can be checked, trying anyway would fail on Discord's side
+    let Some(guild) = ctx.cache.guild(guild_id) else {
+        return Some(tr("PERM_NOT_CACHED", lang, &[]));
+    };
+    let Some(role) = guild.roles.get(&role_id) else {
+        return Some(tr("ROLE_GONE", lang, &[]));
+    };
+    if role.managed {
+        return Some(tr("ROLE_MANAGED", lang, &[]));
+    }
+    if role_id.get() == guild_id.get() {
+        return Some(tr("ROLE_EVERYONE", lang, &[]));
+    }
+
+    // guild wide permissions: @everyone plus every role of the member
+    let permissions = guild
+        .roles
+        .get(&RoleId::new(guild_id.get()))
+        .map_or(Permissions::empty(), |r| r.permissions)
+        | bot_member
+            .roles
+            .iter()
+            .filter_map(|id| guild.roles.get(id))
+            .fold(Permissions::empty(), |acc, r| acc | r.permissions);
+    if guild.owner_id != bot_id && !permissions.administrator() && !permissions.manage_roles() {
+        return Some(tr("ROLE_NO_MANAGE", lang, &[]));
+    }
+
+    let highest = guild
+        .member_highest_role(&bot_member)
+        .map_or(0, |r| r.position);
+    if guild.owner_id != bot_id && highest <= role.position {
+        return Some(tr("ROLE_TOO_HIGH", lang, &[("role", &role_id)]));
+    }
+
+    None
+}
+
+async fn warn_blocked(guild_id: u64, role_id: u64, blocker: &str) {
+    let mut warned = BLOCKED_WARNED.lock().await;
+    let now = Instant::now();
+    if warned
+        .get(&(guild_id, role_id))
+        .is_some_and(|last| now.duration_since(*last) < BLOCKED_WARN_EVERY)
+    {
+        return;
+    }
+    warned.insert((guild_id, role_id), now);
+    log_warn(&format!(
+        "Can't manage reward role {role_id} in Guild{guild_id}: {blocker}"
+    ));
+}
+
+async fn grant(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    role_id: u64,
+    user_id: u64,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    let guild = GuildId::new(guild_id);
+    let role = RoleId::new(role_id);
+    if let Some(blocker) = role_blocker(ctx, guild, role, "en").await {
+        warn_blocked(guild_id, role_id, &blocker).await;
+        return Ok(());
+    }
+
+    match ctx
+        .http
+        .add_member_role(guild, UserId::new(user_id), role, Some(reason))
+        .await
+    {
+        Ok(()) => {
+            add_reward_holder(pool, guild_id, role_id, user_id).await?;
+            set_holder(guild_id, role_id, user_id, true).await;
+            add_reward_audit(pool, guild_id, user_id, role_id, "granted", reason).await?;
+            log_info(&format!(
+                "🏅 Granted role {role_id} to User{user_id} in Guild{guild_id} ({reason})"
+            ));
+        }
+        Err(e) => {
+            crate::metrics::discord_error(&e);
+            add_reward_audit(
+                pool,
+                guild_id,
+                user_id,
+                role_id,
+                "failed",
+                &format!("grant ({reason}): {e}"),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+// Returns true once the user no longer holds the role
+async fn revoke(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    role_id: u64,
+    user_id: u64,
+    reason: &str,
+) -> Result<bool, sqlx::Error> {
+    let guild = GuildId::new(guild_id);
+    let role = RoleId::new(role_id);
+    if let Some(blocker) = role_blocker(ctx, guild, role, "en").await {
+        warn_blocked(guild_id, role_id, &blocker).await;
+        return Ok(false);
+    }
+
+    let result = ctx
+        .http
+        .remove_member_role(guild, UserId::new(user_id), role, Some(reason))
+        .await;
+    // members who left can't hold the role anymore, forget them
+    let left = matches!(
+        &result,
+        Err(serenity::Error::Http(HttpError::UnsuccessfulRequest(res)))
+            if res.status_code == StatusCode::NOT_FOUND
+    );
+
+    match result {
+        Ok(()) => {
+            remove_reward_holder(pool, guild_id, role_id, user_id).await?;
+            set_holder(guild_id, role_id, user_id, false).await;
+            add_reward_audit(pool, guild_id, user_id, role_id, "revoked", reason).await?;
+            log_info(&format!(
+                "🏅 Revoked role {role_id} from User{user_id} in Guild{guild_id} ({reason})"
+            ));
+            Ok(true)
+        }
+        Err(_) if left => {
+            remove_reward_holder(pool, guild_id, role_id, user_id).await?;
+            set_holder(guild_id, role_id, user_id, false).await;
+            let reason = format!("{reason} (member left)");
+            add_reward_audit(pool, guild_id, user_id, role_id, "revoked", &reason).await?;
+            Ok(true)
+        }
+        Err(e) => {
+            crate::metrics::discord_error(&e);
+            add_reward_audit(
+                pool,
+                guild_id,
+                user_id,
+                role_id,
+                "failed",
+                &format!("revoke ({reason}): {e}"),
+            )
+            .await?;
+            Ok(false)
+        }
+    }
+}
+
+/// Tallies a valid count of `user_id` and grants/revokes reward roles accordingly
+pub async fn on_valid_count(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    rewards: &[RoleReward],
+    user_id: u64,
+    count: i64,
+    month: &str,
+) {
+    if let Err(e) = apply_rewards(ctx, pool, guild_id, rewards, user_id, count, month).await {
+        log_error(&format!("Role rewards failed for Guild{guild_id}: {e}"));
+    }
+}
+
+async fn apply_rewards(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    rewards: &[RoleReward],
+    user_id: u64,
+    count: i64,
+    month: &str,
+) -> Result<(), sqlx::Error> {
+    record_user_count(pool, guild_id, user_id, month).await?;
+    if rewards.is_empty() {
+        return Ok(());
+    }
+
+    // only what the configured rewards need is queried, this runs on every count
+    let lifetime = if rewards.iter().any(|r| r.kind == RewardKind::LifetimeCounts) {
+        lifetime_counts(pool, guild_id, user_id).await?
+    } else {
+        0
+    };
+    let mut ranking = None;
+
+    for reward in rewards {
+        if reward.kind == RewardKind::Milestone && count != reward.threshold {
+            continue;
+        }
+        let holders = holders(pool, guild_id, reward.role_id).await?;
+        let holds = holders.contains(&user_id);
+
+        match reward.kind {
+            RewardKind::LifetimeCounts => {
+                if lifetime >= reward.threshold && !holds {
+                    let reason = format!("reached {} lifetime counts", reward.threshold);
+                    grant(ctx, pool, guild_id, reward.role_id, user_id, &reason).await?;
+                } else if lifetime < reward.threshold && holds {
+                    let reason = format!("below {} lifetime counts", reward.threshold);
+                    revoke(ctx, pool, guild_id, reward.role_id, user_id, &reason).await?;
+                }
+            }
+
+            RewardKind::Milestone => {
+                if !holds {
+                    let reason = format!("sent milestone count {}", reward.threshold);
+                    grant(ctx, pool, guild_id, reward.role_id, user_id, &reason).await?;
+                }
+            }
+
+            RewardKind::MonthlyTop => {
+                let ranking = match &ranking {
+                    Some(ranking) => ranking,
+                    None => ranking.insert(month_ranking(pool, guild_id, month).await?),
+                };
+                let Some(&(_, top_counts)) = ranking.first() else {
+                    continue;
+                };
+                // ties keep whoever got there first
+                let leaders: Vec<u64> = ranking
+                    .iter()
+                    .filter(|(_, counts)| *counts == top_counts)
+                    .map(|(id, _)| *id)
+                    .collect();
+                if holders.iter().any(|h| leaders.contains(h)) {
+                    continue;
+                }
+
+                for holder in holders {
+                    let reason = format!("no longer top counter of {month}");
+                    revoke(ctx, pool, guild_id, reward.role_id, holder, &reason).await?;
+                }
+                if leaders.contains(&user_id) {
+                    let reason = format!("top counter of {month} ({top_counts} counts)");
+                    grant(ctx, pool, guild_id, reward.role_id, user_id, &reason).await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes the role back from everyone who got it from a reward that was just removed
+pub async fn revoke_all(ctx: &Context, pool: &PgPool, guild_id: u64, role_id: u64) -> usize {
+    let holders = match reward_holders(pool, guild_id, role_id).await {
+        Ok(holders) => holders,
+        Err(e) => {
+            log_error(&format!(
+                "Failed loading reward holders of Guild{guild_id}: {e}"
+            ));
+            return 0;
+        }
+    };
+
+    let mut revoked = 0;
+    for user_id in holders {
+        match revoke(ctx, pool, guild_id, role_id, user_id, "reward removed").await {
+            Ok(true) => revoked += 1,
+            Ok(false) => {}
+            Err(e) => log_error(&format!("Failed revoking reward in Guild{guild_id}: {e}")),
+        }
+    }
+    // holders the bot couldn't revoke stay in the table, a re-added reward reloads them
+    HOLDERS.lock().await.remove(&(guild_id, role_id));
+    revoked
+}
+
+/// The guild's current month (`YYYY-MM`) when its monthly top roles haven't been checked for it
+/// yet, marking it checked. `None` without monthly top rewards or when already checked
+pub async fn month_to_roll_over(guild_id: u64, utc: i8, rewards: &[RoleReward]) -> Option<String> {
+    if !rewards.iter().any(|r| r.kind == RewardKind::MonthlyTop) {
+        return None;
+    }
+    let month = (Utc::now() + chrono::Duration::hours(utc as i64))
+        .format("%Y-%m")
+        .to_string();
+
+    let mut checked = CHECKED_MONTHS.lock().await;
+    if checked.get(&guild_id) == Some(&month) {
+        return None;
+    }
+    checked.insert(guild_id, month.clone());
+    Some(month)
+}
+
+/// Takes monthly top roles back from last month's holders once a new month started,
+/// whoever leads the new month gets it with their next count
+pub async fn roll_over_month(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    rewards: &[RoleReward],
+    month: &str,
+) {
+    if let Err(e) = revoke_past_tops(ctx, pool, guild_id, rewards, month).await {
+        log_error(&format!(
+            "Monthly rewards rollover failed for Guild{guild_id}: {e}"
+        ));
+        // checked again on the next scheduler tick
+        CHECKED_MONTHS.lock().await.remove(&guild_id);
+    }
+}
+
+async fn revoke_past_tops(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: u64,
+    rewards: &[RoleReward],
+    month: &str,
+) -> Result<(), sqlx::Error> {
+    let ranking = month_ranking(pool, guild_id, month).await?;
+    let top_counts = ranking.first().map(|(_, counts)| *counts);
+
+    for reward in rewards.iter().filter(|r| r.kind == RewardKind::MonthlyTop) {
+        for holder in holders(pool, guild_id, reward.role_id).await? {
+            // already on top of the new month
+            if ranking
+                .iter()
+                .any(|(id, counts)| *id == holder && Some(*counts) == top_counts)
+            {
+                continue;
+            }
+            let reason = format!("not top counter of {month}");
+            revoke(ctx, pool, guild_id, reward.role_id, holder, &reason).await?;
+        }
+    }
+    Ok(())
+}
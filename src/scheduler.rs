@@ -0,0 +1,504 @@
+use crate::commands::relog::auto_relog_guild;
+use crate::data::structs::GuildData;
+use crate::data::{BotData, load_all_data, load_guild_data, load_schedules, update_guild_data};
+use crate::permissions::{
+    COUNTING_PERMISSIONS, LOG_PERMISSIONS, describe_missing, missing_permissions,
+};
+use crate::utils::{log_error, log_info, log_warn, notify_guild, tr};
+use once_cell::sync::Lazy;
+use serenity::all::{ChannelId, CreateMessage, GuildId};
+use serenity::prelude::*;
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::hash::BuildHasher;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Duration, Instant, MissedTickBehavior, interval, sleep};
+
+// How often the scheduler checks which guilds are due
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+// Failures are capped so the back-off exponent can't overflow
+const MAX_BACKOFF_STEPS: u32 = 10;
+
+struct SchedulerConfig {
+    concurrency: usize,
+    jitter: Duration,
+    max_backoff: Duration,
+    live_debounce: Duration,
+}
+impl SchedulerConfig {
+    fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> T {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+
+        Self {
+            concurrency: var("AUTO_RELOG_CONCURRENCY", 4usize).max(1),
+            jitter: Duration::from_secs(var("AUTO_RELOG_JITTER_SECS", 30u64)),
+            max_backoff: Duration::from_secs(var("AUTO_RELOG_MAX_BACKOFF_MINS", 360u64) * 60),
+            live_debounce: Duration::from_secs(var("LIVE_UPDATE_DEBOUNCE_SECS", 15u64)),
+        }
+    }
+}
+
+struct GuildSchedule {
+    // `None` when auto relog is off, the guild then only updates on counting activity
+    next_run: Option<Instant>,
+    last_run: Option<Instant>,
+    failures: u32,
+    running: bool,
+    // past years whose counts changed and need their logs re-rendered
+    dirty_years: BTreeSet<i32>,
+}
+
+impl GuildSchedule {
+    fn idle() -> Self {
+        Self {
+            next_run: None,
+            last_run: None,
+            failures: 0,
+            running: false,
+            dirty_years: BTreeSet::new(),
+        }
+    }
+}
+
+static CONFIG: Lazy<SchedulerConfig> = Lazy::new(SchedulerConfig::from_env);
+
+static SCHEDULES: Lazy<Mutex<HashMap<u64, GuildSchedule>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// The one and only scheduler task
+static SUPERVISOR: Lazy<Mutex<Option<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(None));
+static CATCHING_UP: AtomicBool = AtomicBool::new(false);
+static LAST_TICK: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+fn jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let random = RandomState::new().hash_one(Instant::now());
+    Duration::from_millis(random % max.as_millis() as u64)
+}
+
+fn backoff(interval: Duration, failures: u32, max: Duration) -> Duration {
+    interval
+        .saturating_mul(2u32.saturating_pow(failures.min(MAX_BACKOFF_STEPS)))
+        .min(max.max(interval))
+}
+
+/// When the scheduler last woke up to scan, `None` if it never ran
+pub async fn last_tick() -> Option<Instant> {
+    *LAST_TICK.lock().await
+}
+
+/// Makes the guild due on the next scan (e.g. after its interval changed)
+pub async fn reschedule(guild_id: u64) {
+    if let Some(schedule) = SCHEDULES.lock().await.get_mut(&guild_id) {
+        schedule.next_run = Some(Instant::now());
+        schedule.failures = 0;
+    }
+}
+
+/// Pulls the guild's next update forward after counting activity,
+/// at most once per `LIVE_UPDATE_DEBOUNCE_SECS`
+pub async fn request_update(guild_id: u64) {
+    let mut schedules = SCHEDULES.lock().await;
+    // guilds without auto relog get an entry on their first count
+    let schedule = schedules
+        .entry(guild_id)
+        .or_insert_with(GuildSchedule::idle);
+    // failing guilds keep their back-off
+    if schedule.failures > 0 {
+        return;
+    }
+
+    let now = Instant::now();
+    let earliest = schedule
+        .last_run
+        .map_or(now, |last| (last + CONFIG.live_debounce).max(now));
+    if schedule.next_run.is_none_or(|next| earliest < next) {
+        schedule.next_run = Some(earliest);
+    }
+}
+
+/// Marks years for a log refresh on the guild's next (debounced) update
+pub async fn request_refresh(guild_id: u64, years: BTreeSet<i32>) {
+    SCHEDULES
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_insert_with(GuildSchedule::idle)
+        .dirty_years
+        .extend(years);
+    request_update(guild_id).await;
+}
+
+/// Called on every gateway ready: stores the new context, makes sure exactly one scheduler is running and catches up on missed counts
+pub async fn on_ready(ctx: Context, bot_data: Arc<BotData>) {
+    crate::bot::set_context(ctx).await;
+
+    {
+        let mut supervisor = SUPERVISOR.lock().await;
+        if supervisor
+            .as_ref()
+            .is_none_or(|handle| handle.is_finished())
+        {
+            *supervisor = Some(tokio::spawn(supervise(Arc::clone(&bot_data))));
+        }
+    }
+
+    tokio::spawn(catch_up(bot_data));
+}
+
+// Restart the scheduler if it ever dies
+async fn supervise(bot_data: Arc<BotData>) {
+    loop {
+        let result = tokio::spawn(run(Arc::clone(&bot_data))).await;
+        if crate::shutdown::is_shutting_down() {
+            log_info("⏹️ Scheduler stopped");
+            return;
+        }
+
+        match result {
+            Err(e) if e.is_panic() => log_error("💀 Scheduler panicked"),
+            _ => log_warn("⚠️ Scheduler exited unexpectedly"),
+        }
+
+        // guilds stuck as running would never be scheduled again
+        for schedule in SCHEDULES.lock().await.values_mut() {
+            schedule.running = false;
+        }
+
+        log_info("🔁 Restarting scheduler in 10 seconds…");
+        sleep(Duration::from_secs(10)).await;
+    }
+}
+
+// Scan every set up guild from its last_scanned_msg_id after (re)connecting
+async fn catch_up(bot_data: Arc<BotData>) {
+    if CATCHING_UP.swap(true, Ordering::SeqCst) {
+        log_info("🔁 Catch-up already in progress, skipping");
+        return;
+    }
+
+    let Some(ctx) = crate::bot::current_context().await else {
+        CATCHING_UP.store(false, Ordering::SeqCst);
+        return;
+    };
+
+    match load_all_data(&bot_data.pool).await {
+        Ok(guilds) => {
+            let semaphore = Arc::new(Semaphore::new(CONFIG.concurrency));
+            let mut tasks = JoinSet::new();
+
+            for (guild_id_u64, guild_data) in guilds.0 {
+                if !guild_data.is_setup || crate::shutdown::is_shutting_down() {
+                    continue;
+                }
+
+                let ctx = ctx.clone();
+                let bot_data = Arc::clone(&bot_data);
+                let semaphore = Arc::clone(&semaphore);
+
+                tasks.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    let result =
+                        auto_relog_guild(&ctx, &bot_data, guild_id_u64, &BTreeSet::new(), false)
+                            .await;
+                    (guild_id_u64, result)
+                });
+            }
+
+            let (mut recovered, mut guild_count, mut failed) = (0usize, 0usize, 0usize);
+            while let Some(joined) = tasks.join_next().await {
+                match joined {
+                    Ok((_, Ok(scanned))) => {
+                        // a guild already relogging is caught up by that relog
+                        recovered += scanned.unwrap_or_default();
+                        guild_count += 1;
+                    }
+                    Ok((guild_id_u64, Err(e))) => {
+                        failed += 1;
+                        crate::metrics::discord_error(e.as_ref());
+                        log_warn(&format!("Catch-up failed for Guild{guild_id_u64}: {e}"));
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        log_error(&format!("Catch-up task crashed: {e}"));
+                    }
+                }
+            }
+
+            log_info(&format!(
+                "🔁 Catch-up recovered {recovered} messages across {guild_count} guilds ({failed} failed)"
+            ));
+        }
+        Err(e) => log_error(&format!("Catch-up failed to load guilds: {e}")),
+    }
+
+    CATCHING_UP.store(false, Ordering::SeqCst);
+}
+
+async fn run(bot_data: Arc<BotData>) {
+    let semaphore = Arc::new(Semaphore::new(CONFIG.concurrency));
+
+    let mut scan = interval(SCAN_INTERVAL);
+    scan.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let shutdown = crate::shutdown::token();
+
+    loop {
+        tokio::select! {
+            _ = scan.tick() => {}
+            _ = shutdown.cancelled() => return,
+        }
+        *LAST_TICK.lock().await = Some(Instant::now());
+
+        let Some(ctx) = crate::bot::current_context().await else {
+            continue;
+        };
+
+        // only the few columns deciding what's due, full rows are loaded per due guild
+        let guilds = match load_schedules(&bot_data.pool).await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                log_error(&format!("Scheduler failed to load guilds: {e}"));
+                continue;
+            }
+        };
+
+        crate::metrics::guilds_configured(guilds.iter().filter(|g| g.is_setup).count());
+
+        // last month's top counters lose their role even if nobody counts in the new one
+        for guild in guilds.iter().filter(|g| g.is_setup) {
+            if let Some(month) =
+                crate::rewards::month_to_roll_over(guild.guild_id, guild.utc, &guild.role_rewards)
+                    .await
+            {
+                let ctx = ctx.clone();
+                let bot_data = Arc::clone(&bot_data);
+                let (guild_id_u64, rewards) = (guild.guild_id, guild.role_rewards.clone());
+                tokio::spawn(async move {
+                    crate::rewards::roll_over_month(
+                        &ctx,
+                        &bot_data.pool,
+                        guild_id_u64,
+                        &rewards,
+                        &month,
+                    )
+                    .await;
+                });
+            }
+        }
+
+        let now = Instant::now();
+        let mut schedules = SCHEDULES.lock().await;
+        schedules.retain(|guild_id, schedule| {
+            schedule.running || guilds.iter().any(|g| g.guild_id == *guild_id && g.is_setup)
+        });
+
+        for guild in guilds {
+            if !guild.is_setup {
+                continue;
+            }
+            let guild_id_u64 = guild.guild_id;
+            let periodic = guild.auto_relog;
+
+            let schedule = schedules
+                .entry(guild_id_u64)
+                .or_insert_with(GuildSchedule::idle);
+            // spread first runs so every guild doesn't fire on the same tick
+            if periodic && schedule.next_run.is_none() {
+                schedule.next_run = Some(now + jitter(CONFIG.jitter));
+            }
+            if schedule.running || schedule.next_run.is_none_or(|next| next > now) {
+                continue;
+            }
+            schedule.running = true;
+            schedule.last_run = Some(now);
+            let dirty_years = std::mem::take(&mut schedule.dirty_years);
+
+            let relog_interval = Duration::from_secs(guild.relog_interval.max(1) as u64 * 60);
+            let ctx = ctx.clone();
+            let bot_data = Arc::clone(&bot_data);
+            let semaphore = Arc::clone(&semaphore);
+
+            let years = dirty_years.clone();
+            let work = tokio::spawn(async move {
+                let result = match semaphore.acquire_owned().await {
+                    Ok(_permit) => match load_guild_data(&bot_data.pool, guild_id_u64).await {
+                        Ok(guild_data) => {
+                            let revoked = if periodic {
+                                revoked_permissions(&ctx, guild_id_u64, &guild_data).await
+                            } else {
+                                Vec::new()
+                            };
+                            if !revoked.is_empty() {
+                                SCHEDULES.lock().await.remove(&guild_id_u64);
+                                suspend_auto_relog(&ctx, &bot_data, guild_id_u64, &revoked).await;
+                                return None;
+                            }
+
+                            let _timer = crate::metrics::auto_relog_timer();
+                            // without auto relog this run was asked for by counting activity
+                            auto_relog_guild(&ctx, &bot_data, guild_id_u64, &years, !periodic).await
+                        }
+                        Err(e) => Err(e.into()),
+                    },
+                    Err(e) => Err(e.into()),
+                };
+                Some(result)
+            });
+
+            // a panicking relog must still clear `running`, or the guild is never scheduled again
+            tokio::spawn(async move {
+                let result = match work.await {
+                    Ok(Some(result)) => result,
+                    // suspended, the guild is no longer scheduled
+                    Ok(None) => return,
+                    Err(e) => Err(format!("auto relog task failed: {e}").into()),
+                };
+
+                let mut schedules = SCHEDULES.lock().await;
+                let Some(schedule) = schedules.get_mut(&guild_id_u64) else {
+                    return;
+                };
+                schedule.running = false;
+
+                match result {
+                    Ok(_) => {
+                        schedule.failures = 0;
+                        schedule.next_run = periodic
+                            .then(|| Instant::now() + relog_interval + jitter(CONFIG.jitter));
+                    }
+                    Err(e) => {
+                        crate::metrics::discord_error(e.as_ref());
+                        schedule.dirty_years.extend(dirty_years);
+                        schedule.failures += 1;
+                        let delay = backoff(relog_interval, schedule.failures, CONFIG.max_backoff);
+                        schedule.next_run = Some(Instant::now() + delay);
+
+                        log_warn(&format!(
+                            "Auto relog failed for Guild{} ({} in a row), retrying in {}: {}",
+                            guild_id_u64,
+                            schedule.failures,
+                            humantime::format_duration(Duration::from_secs(delay.as_secs())),
+                            e
+                        ));
+                    }
+                }
+            });
+        }
+    }
+}
+
+// Lookups that fail (outages, cold cache) are left to the relog itself and its back-off,
+// only permissions that are definitely missing count as revoked
+async fn revoked_permissions(
+    ctx: &Context,
+    guild_id_u64: u64,
+    guild_data: &GuildData,
+) -> Vec<String> {
+    let guild_id = GuildId::new(guild_id_u64);
+    let lang = guild_data.settings.lang.as_str();
+    let mut revoked = Vec::new();
+    for channel in &guild_data.channels {
+        let (Some(count_ch_id), Some(log_ch_id)) =
+            (channel.ids.counting_channel_id, channel.ids.log_channel_id)
+        else {
+            continue;
+        };
+        for (channel_id, required) in [
+            (ChannelId::new(count_ch_id), COUNTING_PERMISSIONS),
+            (ChannelId::new(log_ch_id), LOG_PERMISSIONS),
+        ] {
+            if let Ok(missing) =
+                missing_permissions(ctx, guild_id, channel_id, required, lang).await
+                && !missing.is_empty()
+            {
+                // shared log channels would be reported once per counting channel
+                let line = describe_missing(channel_id, missing, lang);
+                if !revoked.contains(&line) {
+                    revoked.push(line);
+                }
+            }
+        }
+    }
+    revoked
+}
+
+// Turns auto relog off and tells the server why, in a log channel if one still takes
+// messages, otherwise by DM to the owner
+async fn suspend_auto_relog(
+    ctx: &Context,
+    bot_data: &BotData,
+    guild_id_u64: u64,
+    revoked: &[String],
+) {
+    let guild_data = match update_guild_data(&bot_data.pool, guild_id_u64, |data| {
+        data.settings.auto_relog = false
+    })
+    .await
+    {
+        Ok(guild_data) => guild_data,
+        Err(e) => {
+            log_error(&format!(
+                "Failed to disable auto relog for Guild{guild_id_u64}: {e}"
+            ));
+            return;
+        }
+    };
+    log_warn(&format!(
+        "Auto relog disabled for Guild{guild_id_u64}, permissions revoked: {}",
+        revoked.join("; ")
+    ));
+
+    let notice = CreateMessage::new().content(tr(
+        "AUTO_RELOG_SUSPENDED",
+        &guild_data.settings.lang,
+        &[("problems", &revoked.join("\n"))],
+    ));
+    let log_channels: BTreeSet<u64> = guild_data
+        .channels
+        .iter()
+        .filter_map(|c| c.ids.log_channel_id)
+        .collect();
+    notify_guild(ctx, GuildId::new(guild_id_u64), log_channels, notice).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn jitter_stays_below_max() {
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(jitter(MIN) < MIN);
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_per_failure() {
+        let max = MIN * 60;
+        assert_eq!(backoff(MIN * 5, 0, max), MIN * 5);
+        assert_eq!(backoff(MIN * 5, 1, max), MIN * 10);
+        assert_eq!(backoff(MIN * 5, 3, max), MIN * 40);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        assert_eq!(backoff(MIN * 5, 4, MIN * 60), MIN * 60);
+        assert_eq!(backoff(MIN * 5, u32::MAX, MIN * 60), MIN * 60);
+        // the cap never cuts below the guild's own interval
+        assert_eq!(backoff(MIN * 90, 2, MIN * 60), MIN * 90);
+    }
+}
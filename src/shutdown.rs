@@ -0,0 +1,71 @@
+use crate::utils::{log_info, log_warn};
+use once_cell::sync::Lazy;
+use std::time::Instant;
+use tokio::time::{Duration, timeout};
+use tokio_util::sync::CancellationToken;
+
+// How long cancelled relog sessions get to wrap up before we stop waiting
+const RELOG_GRACE: Duration = Duration::from_secs(15);
+
+static SHUTDOWN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+
+pub fn token() -> CancellationToken {
+    SHUTDOWN.clone()
+}
+
+pub fn is_shutting_down() -> bool {
+    SHUTDOWN.is_cancelled()
+}
+
+/// Resolves once SIGINT or SIGTERM is received and flags the shutdown
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("❌ Failed to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("❌ Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    let signal = tokio::select! {
+        _ = ctrl_c => "SIGINT",
+        _ = terminate => "SIGTERM",
+        _ = SHUTDOWN.cancelled() => return,
+    };
+
+    log_info(&format!("🛑 Received {signal}, shutting down…"));
+    SHUTDOWN.cancel();
+}
+
+/// Stops background work in order: relogs, pending saves, then the gateway
+pub async fn run(booted_at: Instant) {
+    SHUTDOWN.cancel();
+
+    let cancelled_relogs = crate::commands::relog::cancel_all_relogs().await;
+    if timeout(RELOG_GRACE, crate::commands::relog::wait_for_relogs())
+        .await
+        .is_err()
+    {
+        log_warn("⚠️ Relog sessions didn't finish in time");
+    }
+
+    // held until exit so nothing else gets written half way
+    let (_saves_guard, flushed_saves) = crate::data::flush_saves().await;
+
+    crate::bot::shutdown_shards().await;
+
+    log_info(&format!(
+        "👋 Shutdown complete: cancelled {} relog session(s), flushed {} pending save(s), uptime {}",
+        cancelled_relogs,
+        flushed_saves,
+        humantime::format_duration(Duration::from_secs(booted_at.elapsed().as_secs()))
+    ));
+}
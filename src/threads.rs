@@ -0,0 +1,141 @@
+use crate::data::BotData;
+use crate::data::try_load_guild_data;
+use crate::i18n::DEFAULT_LANG;
+use crate::utils::{log_info, log_warn, notify_guild, tr};
+use serenity::all::{ChannelId, ChannelType, CreateMessage, EditThread, GuildChannel, GuildId};
+use serenity::prelude::Context;
+
+/// Channel kinds that can hold counting or logs: text, announcement and voice/stage chats,
+/// plus threads (forum posts are public threads)
+pub fn countable_channel_types() -> Vec<ChannelType> {
+    vec![
+        ChannelType::Text,
+        ChannelType::News,
+        ChannelType::Voice,
+        ChannelType::Stage,
+        ChannelType::PublicThread,
+        ChannelType::PrivateThread,
+        ChannelType::NewsThread,
+    ]
+}
+
+fn is_thread(kind: ChannelType) -> bool {
+    matches!(
+        kind,
+        ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread
+    )
+}
+
+// Regular channels and active threads sit in the cache, so only archived threads
+// (or channels the cache missed) cost a request
+async fn fetch_if_thread(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<GuildChannel> {
+    {
+        let guild = ctx.cache.guild(guild_id)?;
+        if guild.channels.contains_key(&channel_id) {
+            return None;
+        }
+        if let Some(thread) = guild.threads.iter().find(|t| t.id == channel_id)
+            && !thread.thread_metadata.is_some_and(|m| m.archived)
+        {
+            return None;
+        }
+    }
+
+    let channel = channel_id.to_channel(&ctx.http).await.ok()?.guild()?;
+    is_thread(channel.kind).then_some(channel)
+}
+
+/// Reopens the channel if it's an archived thread so logs can be sent and edited,
+/// `Err` tells the server in `lang` why it's stuck, the cause itself is logged
+pub async fn ensure_writable(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    lang: &str,
+) -> Result<(), String> {
+    let Some(thread) = fetch_if_thread(ctx, guild_id, channel_id).await else {
+        return Ok(());
+    };
+    let Some(meta) = thread.thread_metadata else {
+        return Ok(());
+    };
+    if !meta.archived {
+        return Ok(());
+    }
+
+    match channel_id
+        .edit_thread(&ctx.http, EditThread::new().archived(false))
+        .await
+    {
+        Ok(_) => {
+            log_info(&format!(
+                "🧵 Unarchived log thread {channel_id} in Guild{guild_id}"
+            ));
+            Ok(())
+        }
+        Err(e) => {
+            crate::metrics::discord_error(&e);
+            log_warn(&format!(
+                "Couldn't reopen log thread {channel_id} in Guild{guild_id}: {e}"
+            ));
+            let key = if meta.locked {
+                "THREAD_LOCKED_REOPEN_FAILED"
+            } else {
+                "THREAD_REOPEN_FAILED"
+            };
+            Err(tr(key, lang, &[("channel", &channel_id)]))
+        }
+    }
+}
+
+/// Joins the channel if it's a thread, private threads are invisible to non members
+pub async fn join_if_thread(ctx: &Context, channel_id: ChannelId) {
+    let Ok(channel) = channel_id.to_channel(&ctx.http).await else {
+        return;
+    };
+    if channel.guild().is_some_and(|c| is_thread(c.kind))
+        && let Err(e) = channel_id.join_thread(&ctx.http).await
+    {
+        log_warn(&format!("Failed to join thread {channel_id}: {e}"));
+    }
+}
+
+/// Log threads are reopened right away when they get archived, counting threads
+/// are left to the server (they reopen with the next count) but get a warning
+pub async fn on_thread_update(ctx: &Context, bot_data: &BotData, thread: &GuildChannel) {
+    if !thread.thread_metadata.is_some_and(|m| m.archived) {
+        return;
+    }
+
+    let guild_id = thread.guild_id;
+    let Ok(Some(guild_data)) = try_load_guild_data(&bot_data.pool, guild_id.get()).await else {
+        return;
+    };
+    let thread_id = thread.id.get();
+
+    if guild_data
+        .channels
+        .iter()
+        .any(|c| c.ids.log_channel_id == Some(thread_id))
+    {
+        // nobody to tell, ensure_writable logs why it failed
+        let _ = ensure_writable(ctx, guild_id, thread.id, DEFAULT_LANG).await;
+    }
+
+    if let Some(channel) = guild_data.channel(thread_id) {
+        log_warn(&format!(
+            "Counting thread {thread_id} in Guild{guild_id} was archived, counts resume once it's reopened"
+        ));
+        // logging stops without a word otherwise
+        let notice = CreateMessage::new().content(tr(
+            "COUNTING_THREAD_ARCHIVED",
+            &guild_data.settings.lang,
+            &[("channel", &thread_id)],
+        ));
+        notify_guild(ctx, guild_id, channel.ids.log_channel_id, notice).await;
+    }
+}
@@ -1,21 +1,40 @@
-use once_cell::sync::Lazy;
-use serde::Deserialize;
-use serenity::all::MessageFlags;
+use crate::data::{BotData, try_load_guild_data};
+use crate::i18n;
+use serenity::all::{ChannelId, GuildId, Http, MessageFlags, MessageId, RoleId, Timestamp};
 use serenity::builder::*;
 use serenity::model::application::*;
 use serenity::prelude::*;
-use std::collections::HashMap;
 use std::env;
-use std::fs;
+use std::fmt::Display;
+use tracing_subscriber::EnvFilter;
 
+/// `LOG_FORMAT=json` for one JSON object per line (spans included), plain text otherwise.
+/// Levels follow `RUST_LOG`, defaulting to info for the bot and warn for dependencies
+pub fn init_logging() {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("warn,CountLogger=info"));
+
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    if env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json")) {
+        builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init();
+    } else {
+        builder.with_target(false).init();
+    }
+}
+
+// Events pick up the guild/command/relog session of the span they're logged in
 pub fn log_info(msg: &str) {
-    println!("[INFO]: {}", msg)
+    tracing::info!("{msg}")
 }
 pub fn log_warn(msg: &str) {
-    println!("[WARN]: {}", msg)
+    tracing::warn!("{msg}")
 }
 pub fn log_error(msg: &str) {
-    eprintln!("[ERROR]: {}", msg)
+    tracing::error!("{msg}")
 }
 
 pub async fn internal_err(ctx: &Context, command: &CommandInteraction, err: &str) {
@@ -37,85 +56,231 @@ pub async fn internal_err(ctx: &Context, command: &CommandInteraction, err: &str
     log_error(&msg);
 }
 
-pub async fn check_admin(ctx: &Context, command: &CommandInteraction) -> bool {
+// Discord refuses to bulk delete messages older than two weeks, keep a margin
+const BULK_DELETE_MAX_AGE: i64 = 14 * 24 * 3600 - 60;
+
+pub fn is_bulk_deletable(id: MessageId) -> bool {
+    id.created_at().unix_timestamp() > Timestamp::now().unix_timestamp() - BULK_DELETE_MAX_AGE
+}
+
+/// Deletes the messages, in bulk where Discord allows it, returns how many are gone
+pub async fn delete_messages(http: &Http, channel_id: ChannelId, ids: &[MessageId]) -> usize {
+    let (recent, old): (Vec<MessageId>, Vec<MessageId>) =
+        ids.iter().partition(|id| is_bulk_deletable(**id));
+
+    let mut deleted = 0;
+    let mut singles = old;
+    for chunk in recent.chunks(100) {
+        if chunk.len() > 1 && channel_id.delete_messages(http, chunk).await.is_ok() {
+            deleted += chunk.len();
+        } else {
+            singles.extend_from_slice(chunk);
+        }
+    }
+    for id in singles {
+        if channel_id.delete_message(http, id).await.is_ok() {
+            deleted += 1;
+        }
+    }
+    deleted
+}
+
+/// Posts a notice in the first of `log_channels` that takes it, or DMs the guild owner
+pub async fn notify_guild(
+    ctx: &Context,
+    guild_id: GuildId,
+    log_channels: impl IntoIterator<Item = u64>,
+    notice: CreateMessage,
+) {
+    for log_ch_id in log_channels {
+        if ChannelId::new(log_ch_id)
+            .send_message(&ctx.http, notice.clone())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+
+    let owner_id = ctx.cache.guild(guild_id).map(|g| g.owner_id);
+    if let Some(owner_id) = owner_id
+        && let Err(e) = owner_id.direct_message(&ctx.http, notice).await
+    {
+        log_warn(&format!(
+            "Couldn't notify the owner of Guild{guild_id}: {e}"
+        ));
+    }
+}
+
+/// Full name of the invoked (sub)command, e.g. `relog auto toggle`
+pub fn command_path(command: &CommandInteraction) -> String {
+    let mut path = command.data.name.clone();
+    let mut options = &command.data.options;
+    while let Some(option) = options.first() {
+        match &option.value {
+            CommandDataOptionValue::SubCommandGroup(inner)
+            | CommandDataOptionValue::SubCommand(inner) => {
+                path.push(' ');
+                path.push_str(&option.name);
+                options = inner;
+            }
+            _ => break,
+        }
+    }
+    path
+}
+
+// Commands that stay with admins whatever `/setup permissions` says: managers could otherwise
+// hand themselves every command, or roll permissions back with a snapshot
+const ADMIN_ONLY: [&str; 2] = ["setup permissions", "setup restore"];
+
+pub fn is_admin_only(path: &str) -> bool {
+    ADMIN_ONLY.iter().any(|p| {
+        path.strip_prefix(p)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    })
+}
+
+/// Administrators and `BOT_OWNER_ID` may run everything, others need a manager role
+/// or a role the command's override allows (see `/setup permissions`)
+pub async fn check_admin(ctx: &Context, command: &CommandInteraction, bot_data: &BotData) -> bool {
     let member = command.member.as_ref().unwrap();
 
     if member.permissions.unwrap_or_default().administrator() {
         return true;
     }
 
-    if let Ok(owner_id) = env::var("BOT_OWNER_ID") {
-        if member.user.id.get().to_string() == owner_id {
-            return true;
-        };
+    if let Ok(owner_id) = env::var("BOT_OWNER_ID")
+        && member.user.id.get().to_string() == owner_id
+    {
+        return true;
+    };
+
+    let path = command_path(command);
+    let guild_data = match command.guild_id {
+        Some(guild_id) => try_load_guild_data(&bot_data.pool, guild_id.get())
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+    if !is_admin_only(&path)
+        && let Some(guild_data) = &guild_data
+        && guild_data
+            .settings
+            .access
+            .allowed_roles(&path)
+            .iter()
+            .any(|role_id| member.roles.contains(&RoleId::new(*role_id)))
+    {
+        return true;
     }
+    let lang = reply_lang(
+        command,
+        guild_data.as_ref().map(|g| g.settings.lang.as_str()),
+    );
 
     if let Err(e) = command
         .create_response(
             &ctx.http,
             CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new()
-                    .content("⛔ You need admin privileges to use this command!")
+                    .content(tr("NO_ADMIN", &lang, &[("path", &path)]))
                     .flags(InteractionResponseFlags::EPHEMERAL),
             ),
         )
         .await
     {
-        internal_err(&ctx, &command, &e.to_string()).await;
+        internal_err(ctx, command, &e.to_string()).await;
     }
 
     false
 }
 
-#[derive(Deserialize)]
-struct Dictionary(HashMap<String, HashMap<String, String>>);
-
-static DICTIONARY: Lazy<HashMap<String, HashMap<String, String>>> = Lazy::new(|| {
-    let data = fs::read_to_string("src/dictionary.json").expect("Failed to read 'dictionary.json'");
-    let dict: Dictionary = serde_json::from_str(&data).expect("Failed to parse 'dictionary.json'");
-    dict.0
-});
-pub enum CharaCase {
-    Upper,
-    Normal,
-    Lower,
-}
-fn apply_case(word: &str, case: &CharaCase) -> String {
-    match case {
-        CharaCase::Upper => word.to_uppercase(),
-        CharaCase::Lower => word.to_lowercase(),
-        CharaCase::Normal => word.to_string(),
-    }
-}
-
-fn lookup<'a>(dict: &'a HashMap<String, String>, lang: &str) -> Option<&'a str> {
-    dict.get(lang)
-        .map(|s| s.as_str())
-        .or_else(|| dict.get("en").map(|s| s.as_str()))
-}
-
-pub fn get_word(word: &str, lang1: &str, lang2: Option<&str>, case: CharaCase) -> String {
-    let word_dict = match DICTIONARY.get(&word.to_uppercase()) {
-        Some(d) => d,
-        None => return "<null>".to_string(),
-    };
-
+pub fn get_word(word: &str, lang1: &str, lang2: Option<&str>) -> String {
     let mut result = String::new();
 
     // lang1
-    let primary = lookup(word_dict, lang1).unwrap_or("<null>");
-    result.push_str(&apply_case(primary, &case));
+    result.push_str(i18n::word(word, lang1).unwrap_or("<null>"));
 
     // lang2
     if let Some(lang2) = lang2 {
-        let secondary = lookup(word_dict, lang2).unwrap_or("<null>");
         result.push('/');
-        result.push_str(&apply_case(secondary, &case));
+        result.push_str(i18n::word(word, lang2).unwrap_or("<null>"));
     }
 
     result
 }
 
+/// Language pack of a Discord locale, "ja" -> "jp", the default language for unknown ones
+pub fn locale_lang(locale: &str) -> String {
+    i18n::from_locale(locale)
+        .unwrap_or(i18n::DEFAULT_LANG)
+        .to_string()
+}
+
+/// Language to reply in: the invoking user's if a pack serves it, else the server's
+pub fn reply_lang(command: &CommandInteraction, guild_lang: Option<&str>) -> String {
+    i18n::from_locale(&command.locale)
+        .or(guild_lang)
+        .unwrap_or(i18n::DEFAULT_LANG)
+        .to_string()
+}
+
+/// `reply_lang` for commands that don't load the server's data themselves
+pub async fn command_lang(command: &CommandInteraction, bot_data: &BotData) -> String {
+    let guild_data = match command.guild_id {
+        Some(guild_id) => try_load_guild_data(&bot_data.pool, guild_id.get())
+            .await
+            .ok()
+            .flatten(),
+        None => None,
+    };
+    reply_lang(
+        command,
+        guild_data.as_ref().map(|g| g.settings.lang.as_str()),
+    )
+}
+
+/// Text in `lang` (the default language when missing) with its `{name}` placeholders filled in
+pub fn tr(key: &str, lang: &str, args: &[(&str, &(dyn Display + Sync))]) -> String {
+    let Some(text) = i18n::word(key, lang) else {
+        return "<null>".to_string();
+    };
+    args.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+}
+
+/// Slash command described in every language pack
+pub fn slash_command(name: &str, key: &str) -> CreateCommand {
+    i18n::localizations(key).into_iter().fold(
+        CreateCommand::new(name).description(tr(key, i18n::DEFAULT_LANG, &[])),
+        |command, (locale, text)| command.description_localized(locale, text),
+    )
+}
+
+/// Option described in every language pack
+pub fn slash_option(kind: CommandOptionType, name: &str, key: &str) -> CreateCommandOption {
+    i18n::localizations(key).into_iter().fold(
+        CreateCommandOption::new(kind, name, tr(key, i18n::DEFAULT_LANG, &[])),
+        |option, (locale, text)| option.description_localized(locale, text),
+    )
+}
+
+/// String choice named in every language pack
+pub fn localized_choice(
+    option: CreateCommandOption,
+    key: &str,
+    value: &str,
+) -> CreateCommandOption {
+    option.add_string_choice_localized(
+        tr(key, i18n::DEFAULT_LANG, &[]),
+        value,
+        i18n::localizations(key),
+    )
+}
+
 pub fn get_utc_format(utc: &i8) -> String {
     if *utc < 0 {
         "".to_owned() + &utc.to_string()
@@ -123,3 +288,55 @@ pub fn get_utc_format(utc: &i8) -> String {
         "+".to_owned() + &utc.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(data: serde_json::Value) -> CommandInteraction {
+        serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "application_id": "1",
+            "type": 2,
+            "data": data,
+            "channel_id": "1",
+            "token": "",
+            "version": 1,
+            "locale": "en-US",
+            "entitlements": [],
+            "user": { "id": "1", "username": "", "discriminator": "0000", "avatar": null },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn command_path_follows_subcommands() {
+        let relog = command(serde_json::json!({
+            "id": "1",
+            "name": "relog",
+            "type": 1,
+            "options": [{
+                "name": "auto",
+                "type": 2,
+                "options": [{
+                    "name": "interval",
+                    "type": 1,
+                    "options": [{ "name": "minutes", "type": 4, "value": 5 }],
+                }],
+            }],
+        }));
+        assert_eq!(command_path(&relog), "relog auto interval");
+
+        let ping = command(serde_json::json!({ "id": "1", "name": "ping", "type": 1 }));
+        assert_eq!(command_path(&ping), "ping");
+    }
+
+    #[test]
+    fn is_admin_only_matches_whole_words() {
+        assert!(is_admin_only("setup permissions"));
+        assert!(is_admin_only("setup permissions reset"));
+        assert!(is_admin_only("setup restore"));
+        assert!(!is_admin_only("setup permissionsx"));
+        assert!(!is_admin_only("setup channel"));
+    }
+}
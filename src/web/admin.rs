@@ -0,0 +1,244 @@
+use super::AppState;
+use super::api::ApiError;
+use crate::commands::relog::{auto_relog_guild, spawn_relog};
+use crate::data::stats::guild_stats;
+use crate::data::structs::GuildData;
+use crate::data::{BotData, load_all_data, try_load_guild_data, update_guild_data};
+use crate::utils::{log_error, log_info};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use serenity::prelude::Context;
+use std::collections::BTreeSet;
+use std::env;
+use std::sync::Arc;
+
+// Unset (or empty) token disables the whole admin API
+static ADMIN_TOKEN: Lazy<Option<String>> =
+    Lazy::new(|| env::var("ADMIN_API_TOKEN").ok().filter(|t| !t.is_empty()));
+
+// Owner-only control endpoints, every request needs `Authorization: Bearer <ADMIN_API_TOKEN>`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/guilds", get(list_guilds))
+        .route("/guilds/:guild_id", get(guild))
+        .route("/guilds/:guild_id/relog", post(relog))
+        .route("/guilds/:guild_id/auto_relog", post(auto_relog))
+        .route("/guilds/:guild_id/refresh", post(refresh))
+        .layer(middleware::from_fn(require_token))
+}
+
+async fn require_token(request: Request, next: Next) -> Result<Response, ApiError> {
+    let Some(expected) = ADMIN_TOKEN.as_deref() else {
+        return Err(ApiError::NotFound);
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        return Err(ApiError::Unauthorized);
+    }
+
+    Ok(next.run(request).await)
+}
+
+// Don't leak how much of the token matched through response timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn load_guild(state: &AppState, guild_id: u64) -> Result<GuildData, ApiError> {
+    match try_load_guild_data(&state.pool, guild_id).await {
+        Ok(Some(guild_data)) => Ok(guild_data),
+        Ok(None) => Err(ApiError::NotFound),
+        Err(e) => {
+            log_error(&format!("Admin API failed to load Guild{guild_id}: {e}"));
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+async fn discord_context() -> Result<Context, ApiError> {
+    crate::bot::current_context()
+        .await
+        .ok_or_else(|| ApiError::Unavailable("Discord gateway not connected".to_string()))
+}
+
+fn require_setup(guild_data: &GuildData) -> Result<(), ApiError> {
+    if guild_data.is_setup {
+        Ok(())
+    } else {
+        Err(ApiError::Conflict(
+            "Guild hasn't been setup yet".to_string(),
+        ))
+    }
+}
+
+#[derive(Serialize)]
+pub struct GuildSummary {
+    guild_id: String,
+    is_setup: bool,
+    auto_relog: bool,
+    relog_interval: i32,
+    public_api: bool,
+    total: i64,
+    last_date: Option<String>,
+}
+
+async fn list_guilds(State(state): State<AppState>) -> Result<Json<Vec<GuildSummary>>, ApiError> {
+    let all = load_all_data(&state.pool).await.map_err(|e| {
+        log_error(&format!("Admin API failed to load guilds: {e}"));
+        ApiError::Internal
+    })?;
+
+    let mut guilds: Vec<GuildSummary> = all
+        .0
+        .into_iter()
+        .map(|(guild_id, guild_data)| {
+            let stats = guild_stats(guild_data.primary_counts(), guild_data.primary_mode());
+            GuildSummary {
+                guild_id: guild_id.to_string(),
+                is_setup: guild_data.is_setup,
+                auto_relog: guild_data.settings.auto_relog,
+                relog_interval: guild_data.settings.relog_interval,
+                public_api: guild_data.settings.public_api,
+                total: stats.total,
+                last_date: stats.last_date,
+            }
+        })
+        .collect();
+    guilds.sort_by(|a, b| a.guild_id.cmp(&b.guild_id));
+
+    Ok(Json(guilds))
+}
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Serialize)]
+pub struct GuildResponse {
+    guild_id: String,
+    #[serde(flatten)]
+    data: GuildData,
+}
+
+async fn guild(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+) -> Result<Json<GuildResponse>, ApiError> {
+    let mut data = load_guild(&state, guild_id).await?;
+    // signing keys never leave the bot, not even to its owner
+    for webhook in &mut data.settings.webhooks {
+        webhook.secret = REDACTED.to_string();
+    }
+
+    Ok(Json(GuildResponse {
+        guild_id: guild_id.to_string(),
+        data,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RelogQuery {
+    #[serde(default)]
+    formatonly: bool,
+}
+
+async fn relog(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<RelogQuery>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let guild_data = load_guild(&state, guild_id).await?;
+    require_setup(&guild_data)?;
+    let ctx = discord_context().await?;
+
+    let bot_data = Arc::new(BotData::new(Arc::clone(&state.pool)));
+    if !spawn_relog(ctx, bot_data, guild_id, query.formatonly).await {
+        return Err(ApiError::Conflict(
+            "A relog session is already running".to_string(),
+        ));
+    }
+
+    log_info(&format!("🛠 Admin API started a relog for Guild{guild_id}"));
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "started", "formatonly": query.formatonly })),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct AutoRelogQuery {
+    enabled: Option<bool>,
+}
+
+async fn auto_relog(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<AutoRelogQuery>,
+) -> Result<Json<Value>, ApiError> {
+    // unknown guilds are a 404, not a new row
+    load_guild(&state, guild_id).await?;
+
+    let mut old = false;
+    let guild_data = update_guild_data(&state.pool, guild_id, |data| {
+        old = data.settings.auto_relog;
+        data.settings.auto_relog = query.enabled.unwrap_or(!old);
+    })
+    .await
+    .map_err(|e| {
+        log_error(&format!("Admin API failed to save Guild{guild_id}: {e}"));
+        ApiError::Internal
+    })?;
+    crate::scheduler::reschedule(guild_id).await;
+
+    log_info(&format!(
+        "🛠 Admin API set auto relog `{}` -> `{}` for Guild{}",
+        old, guild_data.settings.auto_relog, guild_id
+    ));
+    Ok(Json(
+        json!({ "old": old, "auto_relog": guild_data.settings.auto_relog }),
+    ))
+}
+
+// Scan new counts and re-render every year's logs right away
+async fn refresh(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+) -> Result<Json<Value>, ApiError> {
+    let guild_data = load_guild(&state, guild_id).await?;
+    require_setup(&guild_data)?;
+    let ctx = discord_context().await?;
+
+    let years: BTreeSet<i32> = guild_data
+        .channels
+        .iter()
+        .flat_map(|c| c.daily_counts.keys())
+        .filter_map(|date| date.get(..4)?.parse().ok())
+        .collect();
+
+    let bot_data = BotData::new(Arc::clone(&state.pool));
+    match auto_relog_guild(&ctx, &bot_data, guild_id, &years, true).await {
+        Ok(Some(scanned)) => Ok(Json(json!({ "status": "refreshed", "scanned": scanned }))),
+        Ok(None) => Err(ApiError::Conflict(
+            "A relog session is already running".to_string(),
+        )),
+        Err(e) => {
+            crate::metrics::discord_error(e.as_ref());
+            log_error(&format!(
+                "Admin API refresh failed for Guild{guild_id}: {e}"
+            ));
+            Err(ApiError::Internal)
+        }
+    }
+}
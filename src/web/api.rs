@@ -0,0 +1,191 @@
+use super::AppState;
+use crate::data::stats::{DayCount, GuildStats, day_counts, guild_stats};
+use crate::data::structs::{CountMode, GuildData};
+use crate::data::try_load_guild_data;
+use crate::utils::log_error;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router, middleware};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+// Read-only endpoints for guilds that opted in with `/setup public_api`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/guilds/:guild_id/counts", get(counts))
+        .route("/guilds/:guild_id/stats", get(stats))
+        .route("/guilds/:guild_id/years/:year", get(year))
+        .layer(middleware::map_response(allow_any_origin))
+}
+
+// community sites fetch these straight from the browser
+async fn allow_any_origin(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    response
+}
+
+pub enum ApiError {
+    NotFound,
+    BadRequest(String),
+    Unauthorized,
+    Conflict(String),
+    Unavailable(String),
+    Internal,
+}
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, msg) = match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Guild not found".to_string()),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            ApiError::Unavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            ApiError::Internal => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal error".to_string(),
+            ),
+        };
+        (status, Json(json!({ "error": msg }))).into_response()
+    }
+}
+
+/// Loads a guild only if it's set up and opted in, anything else looks like a 404
+pub async fn load_public_guild(state: &AppState, guild_id: u64) -> Result<GuildData, ApiError> {
+    match try_load_guild_data(&state.pool, guild_id).await {
+        Ok(Some(guild_data)) if guild_data.is_setup && guild_data.settings.public_api => {
+            Ok(guild_data)
+        }
+        Ok(_) => Err(ApiError::NotFound),
+        Err(e) => {
+            log_error(&format!("API failed to load Guild{guild_id}: {e}"));
+            Err(ApiError::Internal)
+        }
+    }
+}
+
+fn parse_date(value: Option<String>, name: &str) -> Result<Option<String>, ApiError> {
+    value
+        .map(|v| {
+            NaiveDate::parse_from_str(&v, "%Y-%m-%d")
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .map_err(|_| ApiError::BadRequest(format!("`{name}` must be YYYY-MM-DD")))
+        })
+        .transpose()
+}
+
+/// Daily counts of the requested counting channel, the primary one by default
+fn channel_counts(
+    guild_data: &GuildData,
+    channel: Option<u64>,
+) -> Result<(&BTreeMap<String, i64>, CountMode), ApiError> {
+    match channel {
+        Some(id) => guild_data
+            .channel(id)
+            .map(|c| (&c.daily_counts, c.mode))
+            .ok_or_else(|| ApiError::BadRequest(format!("`{id}` is not a counting channel"))),
+        None => Ok((guild_data.primary_counts(), guild_data.primary_mode())),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RangeQuery {
+    from: Option<String>,
+    to: Option<String>,
+    channel: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ChannelQuery {
+    channel: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct CountsResponse {
+    guild_id: String,
+    utc: i8,
+    counts: Vec<DayCount>,
+}
+
+async fn counts(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+    Query(range): Query<RangeQuery>,
+) -> Result<Json<CountsResponse>, ApiError> {
+    let from = parse_date(range.from, "from")?;
+    let to = parse_date(range.to, "to")?;
+    let guild_data = load_public_guild(&state, guild_id).await?;
+
+    Ok(Json(CountsResponse {
+        guild_id: guild_id.to_string(),
+        utc: guild_data.settings.utc,
+        counts: {
+            let (daily_counts, mode) = channel_counts(&guild_data, range.channel)?;
+            day_counts(daily_counts, mode, from.as_deref(), to.as_deref())
+        },
+    }))
+}
+
+#[derive(Serialize)]
+pub struct StatsResponse {
+    guild_id: String,
+    utc: i8,
+    #[serde(flatten)]
+    stats: GuildStats,
+}
+
+async fn stats(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<ChannelQuery>,
+) -> Result<Json<StatsResponse>, ApiError> {
+    let guild_data = load_public_guild(&state, guild_id).await?;
+
+    Ok(Json(StatsResponse {
+        guild_id: guild_id.to_string(),
+        utc: guild_data.settings.utc,
+        stats: {
+            let (daily_counts, mode) = channel_counts(&guild_data, query.channel)?;
+            guild_stats(daily_counts, mode)
+        },
+    }))
+}
+
+#[derive(Serialize)]
+pub struct YearResponse {
+    guild_id: String,
+    utc: i8,
+    year: i32,
+    total_increment: i64,
+    counts: Vec<DayCount>,
+}
+
+async fn year(
+    State(state): State<AppState>,
+    Path((guild_id, year)): Path<(u64, i32)>,
+    Query(query): Query<ChannelQuery>,
+) -> Result<Json<YearResponse>, ApiError> {
+    let guild_data = load_public_guild(&state, guild_id).await?;
+
+    let (daily_counts, mode) = channel_counts(&guild_data, query.channel)?;
+    let counts = day_counts(
+        daily_counts,
+        mode,
+        Some(&format!("{year:04}-01-01")),
+        Some(&format!("{year:04}-12-31")),
+    );
+
+    Ok(Json(YearResponse {
+        guild_id: guild_id.to_string(),
+        utc: guild_data.settings.utc,
+        year,
+        total_increment: counts.iter().map(|d| d.increment).sum(),
+        counts,
+    }))
+}
@@ -0,0 +1,223 @@
+use super::AppState;
+use super::api::load_public_guild;
+use crate::data::load_all_data;
+use crate::data::stats::{DayCount, GuildStats, day_counts, guild_stats};
+use crate::data::structs::CountMode;
+use crate::utils::log_error;
+use askama::Template;
+use axum::Router;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use serde::Deserialize;
+use serenity::all::{ChannelId, GuildId};
+use std::collections::BTreeSet;
+
+// SVG user units, the browser scales the chart to fit
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 240.0;
+
+// Human readable pages for guilds that opted in with `/setup public_api`
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(index))
+        .route("/:guild_id", get(guild))
+}
+
+fn render(template: impl Template) -> Response {
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            log_error(&format!("Dashboard failed to render: {e}"));
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+// Falls back to the id while the gateway isn't connected or the guild isn't cached
+async fn guild_name(guild_id: u64) -> String {
+    crate::bot::current_context()
+        .await
+        .and_then(|ctx| {
+            ctx.cache
+                .guild(GuildId::new(guild_id))
+                .map(|g| g.name.clone())
+        })
+        .unwrap_or_else(|| format!("Guild {guild_id}"))
+}
+
+// Same fallback as `guild_name`, threads the cache doesn't hold show their id too
+async fn channel_name(guild_id: u64, channel_id: u64) -> String {
+    crate::bot::current_context()
+        .await
+        .and_then(|ctx| {
+            ctx.cache.guild(GuildId::new(guild_id)).and_then(|g| {
+                g.channels
+                    .get(&ChannelId::new(channel_id))
+                    .map(|c| c.name.clone())
+            })
+        })
+        .unwrap_or_else(|| channel_id.to_string())
+}
+
+/// Counts made over the days as SVG points, returns (line, filled area)
+fn chart_points(days: &[DayCount]) -> (String, String) {
+    if days.is_empty() {
+        return (String::new(), String::new());
+    }
+    // summed up so backwards channels and restarts still climb
+    let made: Vec<i64> = days
+        .iter()
+        .scan(0, |sum, d| {
+            *sum += d.increment;
+            Some(*sum)
+        })
+        .collect();
+    let span = made.last().copied().unwrap_or_default().max(1) as f64;
+    let step = CHART_WIDTH / (days.len().max(2) - 1) as f64;
+
+    let line = made
+        .iter()
+        .enumerate()
+        .map(|(i, made)| {
+            let x = i as f64 * step;
+            let y = CHART_HEIGHT - *made as f64 / span * CHART_HEIGHT;
+            format!("{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let right = (days.len() - 1) as f64 * step;
+    let area = format!("0,{CHART_HEIGHT} {line} {right:.1},{CHART_HEIGHT}");
+
+    (line, area)
+}
+
+struct GuildSummary {
+    guild_id: u64,
+    name: String,
+    total: i64,
+    days_logged: usize,
+    last_date: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "dashboard/index.html")]
+struct IndexTemplate {
+    guilds: Vec<GuildSummary>,
+}
+
+async fn index(State(state): State<AppState>) -> Response {
+    let all = match load_all_data(&state.pool).await {
+        Ok(all) => all.0,
+        Err(e) => {
+            log_error(&format!("Dashboard failed to load guilds: {e}"));
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let mut guilds = Vec::new();
+    for (guild_id, guild_data) in all {
+        if !guild_data.is_setup || !guild_data.settings.public_api {
+            continue;
+        }
+        // days counted in several channels are logged once
+        let dates: BTreeSet<&String> = guild_data
+            .channels
+            .iter()
+            .flat_map(|c| c.daily_counts.keys())
+            .collect();
+        guilds.push(GuildSummary {
+            guild_id,
+            name: guild_name(guild_id).await,
+            total: guild_data
+                .channels
+                .iter()
+                .map(|c| guild_stats(&c.daily_counts, c.mode).total)
+                .sum(),
+            days_logged: dates.len(),
+            last_date: dates.last().map(|d| d.to_string()),
+        });
+    }
+    guilds.sort_by_key(|g| std::cmp::Reverse(g.total));
+
+    render(IndexTemplate { guilds })
+}
+
+#[derive(Deserialize)]
+struct GuildQuery {
+    year: Option<i32>,
+}
+
+struct ChannelSection {
+    name: String,
+    mode: Option<&'static str>, // only shown for channels that don't count normally
+    stats: GuildStats,
+    days: Vec<DayCount>,
+    chart: String,
+    chart_area: String,
+}
+
+#[derive(Template)]
+#[template(path = "dashboard/guild.html")]
+struct GuildTemplate {
+    guild_id: u64,
+    name: String,
+    utc: i8,
+    years: BTreeSet<String>,
+    channels: Vec<ChannelSection>,
+    chart_width: f64,
+    chart_height: f64,
+}
+
+async fn guild(
+    State(state): State<AppState>,
+    Path(guild_id): Path<u64>,
+    Query(query): Query<GuildQuery>,
+) -> Response {
+    let guild_data = match load_public_guild(&state, guild_id).await {
+        Ok(guild_data) => guild_data,
+        Err(e) => return e.into_response(),
+    };
+
+    let from = query.year.map(|y| format!("{y:04}-01-01"));
+    let to = query.year.map(|y| format!("{y:04}-12-31"));
+
+    let mut channels = Vec::new();
+    for channel in &guild_data.channels {
+        let days = day_counts(
+            &channel.daily_counts,
+            channel.mode,
+            from.as_deref(),
+            to.as_deref(),
+        );
+        let (chart, chart_area) = chart_points(&days);
+        channels.push(ChannelSection {
+            name: match channel.ids.counting_channel_id {
+                Some(id) => channel_name(guild_id, id).await,
+                None => String::new(),
+            },
+            mode: (channel.mode != CountMode::Normal).then(|| channel.mode.name()),
+            stats: guild_stats(&channel.daily_counts, channel.mode),
+            // newest first reads better in a table
+            days: days.into_iter().rev().collect(),
+            chart,
+            chart_area,
+        });
+    }
+
+    render(GuildTemplate {
+        guild_id,
+        name: guild_name(guild_id).await,
+        utc: guild_data.settings.utc,
+        years: guild_data
+            .channels
+            .iter()
+            .flat_map(|c| c.daily_counts.keys())
+            .filter_map(|date| date.get(..4).map(str::to_string))
+            .collect(),
+        channels,
+        chart_width: CHART_WIDTH,
+        chart_height: CHART_HEIGHT,
+    })
+}
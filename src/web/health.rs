@@ -0,0 +1,133 @@
+use super::AppState;
+use crate::utils::log_error;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Serialize;
+use serde_json::{Value, json};
+use serenity::all::ConnectionStage;
+use tokio::time::{Duration, Instant, timeout};
+
+// A query slower than this counts as the database being down
+const DB_TIMEOUT: Duration = Duration::from_secs(3);
+// The scheduler wakes every few seconds, this leaves room for slow scans
+const SCHEDULER_STALE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Serialize)]
+pub struct Health {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    checks: Option<Checks>,
+}
+
+#[derive(Serialize)]
+struct Checks {
+    database: Value,
+    gateway: Value,
+    scheduler: Value,
+    relogs: Value,
+}
+
+fn status(ok: bool) -> &'static str {
+    if ok { "ok" } else { "fail" }
+}
+
+/// Liveness: the process is up and serving requests
+pub async fn live() -> Json<Health> {
+    Json(Health {
+        status: "ok",
+        checks: None,
+    })
+}
+
+/// Readiness: 503 unless the database, gateway and scheduler all work
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<Health>) {
+    let (database_ok, database) = check_database(&state).await;
+    let (gateway_ok, gateway) = check_gateway().await;
+    let (scheduler_ok, scheduler) = check_scheduler().await;
+    // relogs never block readiness, they just tell what's in flight
+    let relogs = json!({
+        "status": "ok",
+        "running": crate::commands::relog::running_relogs().await,
+    });
+
+    let ready = database_ok && gateway_ok && scheduler_ok && !crate::shutdown::is_shutting_down();
+    let code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        code,
+        Json(Health {
+            status: if crate::shutdown::is_shutting_down() {
+                "shutting_down"
+            } else if ready {
+                "ready"
+            } else {
+                "not_ready"
+            },
+            checks: Some(Checks {
+                database,
+                gateway,
+                scheduler,
+                relogs,
+            }),
+        }),
+    )
+}
+
+async fn check_database(state: &AppState) -> (bool, Value) {
+    let started = Instant::now();
+    let result = timeout(DB_TIMEOUT, sqlx::query("select 1").execute(&*state.pool)).await;
+
+    match result {
+        Ok(Ok(_)) => (
+            true,
+            json!({ "status": "ok", "latency_ms": started.elapsed().as_millis() as u64 }),
+        ),
+        // the error can name the host or user, it stays in the log
+        Ok(Err(e)) => {
+            log_error(&format!("Health check database query failed: {e}"));
+            (false, json!({ "status": "fail", "error": "query failed" }))
+        }
+        Err(_) => (false, json!({ "status": "fail", "error": "timed out" })),
+    }
+}
+
+async fn check_gateway() -> (bool, Value) {
+    let Some(shards) = crate::bot::shard_statuses().await else {
+        return (
+            false,
+            json!({ "status": "fail", "error": "client not started" }),
+        );
+    };
+
+    let ok = !shards.is_empty() && shards.iter().all(|s| s.stage == ConnectionStage::Connected);
+    let shards: Vec<Value> = shards
+        .iter()
+        .map(|s| {
+            json!({
+                "id": s.id,
+                "stage": s.stage.to_string(),
+                "latency_ms": s.latency.map(|l| l.as_millis() as u64),
+            })
+        })
+        .collect();
+
+    (ok, json!({ "status": status(ok), "shards": shards }))
+}
+
+async fn check_scheduler() -> (bool, Value) {
+    let Some(last_tick) = crate::scheduler::last_tick().await else {
+        return (false, json!({ "status": "fail", "error": "not started" }));
+    };
+
+    let age = last_tick.elapsed();
+    let ok = age <= SCHEDULER_STALE_AFTER;
+    (
+        ok,
+        json!({ "status": status(ok), "last_tick_secs_ago": age.as_secs() }),
+    )
+}
@@ -0,0 +1,37 @@
+pub mod admin;
+pub mod api;
+pub mod dashboard;
+pub mod health;
+
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::{Router, routing::get};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: Arc<PgPool>,
+}
+
+async fn metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        crate::metrics::render().await,
+    )
+}
+
+pub fn router(pool: PgPool) -> Router {
+    Router::new()
+        .route("/", get(|| async { "📊 CountLogger Online 💙" }))
+        .route("/health", get(|| async { "ok" }))
+        .route("/health/live", get(health::live))
+        .route("/health/ready", get(health::ready))
+        .route("/metrics", get(metrics))
+        .nest("/api", api::router())
+        .nest("/admin", admin::router())
+        .nest("/dashboard", dashboard::router())
+        .with_state(AppState {
+            pool: Arc::new(pool),
+        })
+}